@@ -0,0 +1,63 @@
+//! Search over synthetic indexes of 1k, 5k and 20k entries. The char mask
+//! prefilter shows in `no_match`, where most entries lack a query letter.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use locksearch::indexer::{ProgramEntry, ProgramSource, UsageMap};
+use locksearch::search::{normalize, SearchEngine};
+use std::path::PathBuf;
+
+const SIZES: [usize; 3] = [1_000, 5_000, 20_000];
+
+const WORDS: [&str; 16] = [
+    "Visual", "Studio", "Code", "Microsoft", "Edge", "Office", "Word", "Excel", "Adobe", "Reader",
+    "Steam", "Firefox", "Notepad", "Git", "Python", "Terminal",
+];
+
+/// `count` entries named from `WORDS` plus a number, spread over the sources
+fn entries(count: usize) -> Vec<ProgramEntry> {
+    (0..count)
+        .map(|i| {
+            let display_name = format!(
+                "{} {} {}",
+                WORDS[i % WORDS.len()],
+                WORDS[(i / WORDS.len()) % WORDS.len()],
+                i
+            );
+            let source = match i % 3 {
+                0 => ProgramSource::StartMenu,
+                1 => ProgramSource::Desktop,
+                _ => ProgramSource::ProgramFiles,
+            };
+            ProgramEntry {
+                path: PathBuf::from(format!("C:\\Apps\\{}\\app{}.exe", WORDS[i % WORDS.len()], i)),
+                name: format!("app{}", i),
+                display_name: display_name.clone(),
+                base_name: None,
+                source,
+                icon_path: None,
+                target: None,
+                target_name: Some(normalize(&display_name)),
+                modified: None,
+                opener: None,
+            }
+        })
+        .collect()
+}
+
+fn bench_search(c: &mut Criterion) {
+    let engine = SearchEngine::new();
+    let usage = UsageMap::new();
+    let mut group = c.benchmark_group("search");
+    for size in SIZES {
+        let entries = entries(size);
+        for (label, query) in [("prefix", "code"), ("fuzzy", "vsc"), ("no_match", "jqz")] {
+            group.bench_with_input(BenchmarkId::new(label, size), &entries, |b, entries| {
+                b.iter(|| engine.search(black_box(query), entries, &usage, 10, || false))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_search);
+criterion_main!(benches);
@@ -0,0 +1,96 @@
+//! Command-line flags for running the indexer headless (e.g. from a scheduled
+//! task) without opening the launcher window.
+
+use crate::config::Config;
+use crate::indexer::{IndexOptions, ProgramIndex};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "Usage: locksearch [--reindex | --print-index | --export <file>]
+
+  (no flags)        Open the launcher
+  --reindex         Rebuild the index cache and exit
+  --print-index     Rebuild the index and print it to stdout as JSON
+  --export <file>   Write the index to <file> as JSON (the cache if there
+                    is one, else a fresh index)";
+
+/// What the process was asked to do
+pub enum Mode {
+    Gui,
+    Reindex,
+    PrintIndex,
+    Export(PathBuf),
+    Help,
+}
+
+impl Mode {
+    /// Parse process arguments (excluding the program name)
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut mode = Mode::Gui;
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            mode = match arg.as_str() {
+                "--reindex" => Mode::Reindex,
+                "--print-index" => Mode::PrintIndex,
+                "--export" => match args.next() {
+                    Some(file) => Mode::Export(PathBuf::from(file)),
+                    None => return Err("--export needs a file to write".to_string()),
+                },
+                "--help" | "-h" => Mode::Help,
+                other => return Err(format!("Unknown argument: {}", other)),
+            };
+        }
+        Ok(mode)
+    }
+}
+
+/// Run a headless mode to completion and return the process exit code
+pub fn run(mode: Mode, config: &Config) -> i32 {
+    if let Mode::Help = mode {
+        println!("{}", USAGE);
+        return 0;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start runtime: {}", e);
+            return 1;
+        }
+    };
+
+    runtime.block_on(async {
+        let index = ProgramIndex::new(config.cache_dir());
+        // Exporting is happy with the cache; the other modes exist to rebuild it
+        let reindex = match mode {
+            Mode::Export(_) => index.load_cache().await.is_none(),
+            _ => true,
+        };
+        if reindex {
+            if let Some(mut progress) = index.start_indexing(IndexOptions::from_config(config)).await {
+                // The channel closes once the index and cache are written
+                while progress.changed().await.is_ok() {}
+            }
+        }
+
+        if let Mode::Export(path) = &mode {
+            match index.export_json(path).await {
+                Ok(count) => eprintln!("Exported {} entries to {}", count, path.display()),
+                Err(e) => {
+                    eprintln!("Failed to export the index to {}: {}", path.display(), e);
+                    return 1;
+                }
+            }
+        }
+
+        if let Mode::PrintIndex = mode {
+            match serde_json::to_string_pretty(&*index.get_entries().await) {
+                Ok(json) => println!("{}", json),
+                Err(e) => {
+                    eprintln!("Failed to serialize index: {}", e);
+                    return 1;
+                }
+            }
+        }
+        0
+    })
+}
@@ -1,6 +1,25 @@
+use crate::indexer::ProgramSource;
+use crate::search::MAX_RESULTS_LIMIT;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Smallest window the layout fits in; also the window's minimum size
+pub const MIN_WINDOW_WIDTH: f32 = 400.0;
+pub const MIN_WINDOW_HEIGHT: f32 = 300.0;
+
+/// Largest accepted window size (an 8K screen)
+const MAX_WINDOW_WIDTH: f32 = 7680.0;
+const MAX_WINDOW_HEIGHT: f32 = 4320.0;
+
+/// Supported config file names, in order of precedence when several exist
+const CONFIG_FILE_NAMES: [&str; 3] = ["config.yaml", "config.toml", "config.json"];
+
+/// Prefix of environment variables overriding config values, e.g. `LOCKSEARCH_MAX_RESULTS`
+const ENV_PREFIX: &str = "LOCKSEARCH_";
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +31,10 @@ pub struct Config {
     /// Window height
     #[serde(default = "default_window_height")]
     pub window_height: f32,
+
+    /// Scale sizes with the display's DPI setting; off, they are raw pixels
+    #[serde(default = "default_respect_dpi")]
+    pub respect_dpi: bool,
     
     /// Search icon size
     #[serde(default = "default_search_icon_size")]
@@ -24,6 +47,14 @@ pub struct Config {
     /// Maximum results to show
     #[serde(default = "default_max_results")]
     pub max_results: usize,
+
+    /// Minimum fuzzy match score; higher values mean stricter matching
+    #[serde(default)]
+    pub min_score: i64,
+
+    /// Queries shorter than this show the idle list instead of searching
+    #[serde(default = "default_min_query_length")]
+    pub min_query_length: usize,
     
     /// Theme colors
     #[serde(default)]
@@ -31,19 +62,433 @@ pub struct Config {
     
     /// Directories to index (in addition to defaults)
     #[serde(default)]
-    pub extra_index_paths: Vec<String>,
+    pub extra_index_paths: Vec<IndexPath>,
+
+    /// How many folder levels to scan below each Start Menu directory
+    #[serde(default = "default_start_menu_depth")]
+    pub start_menu_depth: usize,
+
+    /// How many folder levels to scan below each Program Files directory
+    #[serde(default = "default_program_files_depth")]
+    pub program_files_depth: usize,
+
+    /// Index the user's and the common Start Menu programs folders
+    #[serde(default = "default_index_start_menu")]
+    pub index_start_menu: bool,
+
+    /// Index executables in the Program Files folders (extra paths are
+    /// indexed either way)
+    #[serde(default = "default_index_program_files")]
+    pub index_program_files: bool,
+
+    /// Index shortcuts on the user's and the public Desktop
+    #[serde(default = "default_index_desktop")]
+    pub index_desktop: bool,
+
+    /// Index shortcuts in the user's Downloads folder
+    #[serde(default)]
+    pub index_downloads: bool,
     
+    /// Installer/updater executable names skipped outside the Start Menu
+    #[serde(default = "default_blocklist_patterns")]
+    pub blocklist_patterns: Vec<String>,
+
+    /// Name parts of helper executables (crash handlers, services) skipped in
+    /// Program Files and extra paths
+    #[serde(default = "default_program_files_name_blocklist")]
+    pub program_files_name_blocklist: Vec<String>,
+
+    /// Walk into symlinked/junctioned directories while indexing
+    #[serde(default)]
+    pub follow_links: bool,
+
+    /// Icons extracted at the same time while indexing
+    #[serde(default = "default_icon_extraction_concurrency")]
+    pub icon_extraction_concurrency: usize,
+
+    /// Size the icon cache is trimmed to after indexing, in megabytes (0: unlimited).
+    /// Icons of indexed programs are kept even past it.
+    #[serde(default = "default_max_icon_cache_mb")]
+    pub max_icon_cache_mb: u64,
+
+    /// Least time between indexing progress updates, in milliseconds
+    #[serde(default = "default_indexing_progress_ms")]
+    pub indexing_progress_ms: u64,
+
     /// Directories to exclude from indexing
     #[serde(default)]
     pub exclude_paths: Vec<String>,
+
+    /// File extensions never indexed, even where a source would include them
+    #[serde(default)]
+    pub exclude_extensions: Vec<String>,
     
-    /// Initial sort order: "alphabetical" or "random"
+    /// Sources listed before anything is typed (empty: all)
+    #[serde(default)]
+    pub empty_query_sources: Vec<ProgramSource>,
+
+    /// Initial sort order: "alphabetical", "random" or "recent" (newest first)
     #[serde(default = "default_initial_sort")]
     pub initial_sort: String,
 
     /// Enable index caching for instant startup
     #[serde(default = "default_enable_cache")]
     pub enable_cache: bool,
+
+    /// Whether a start with a cached index also rescans in the background
+    #[serde(default)]
+    pub reindex_on_startup: ReindexOnStartup,
+
+    /// Age in hours after which the cache counts as stale
+    #[serde(default = "default_reindex_stale_hours")]
+    pub reindex_stale_hours: u64,
+
+    /// Folder for the index cache and extracted icons (default: the user cache dir)
+    #[serde(default)]
+    pub cache_dir: Option<String>,
+
+    /// List a folder's contents when the query is a folder path ending in `\`
+    #[serde(default)]
+    pub path_navigation: bool,
+
+    /// Match query words against the folder names of an entry's location
+    #[serde(default)]
+    pub path_component_matching: bool,
+
+    /// Group results under per-source section headers
+    #[serde(default)]
+    pub group_by_source: bool,
+
+    /// Display names of programs pinned to the top of results
+    #[serde(default)]
+    pub pinned: Vec<String>,
+
+    /// Display names of programs never shown in results (case-insensitive)
+    #[serde(default)]
+    pub hidden_programs: Vec<String>,
+
+    /// Whether letter case matters when matching
+    #[serde(default)]
+    pub case_sensitivity: CaseSensitivity,
+
+    /// How results with equal scores are ordered
+    #[serde(default)]
+    pub tiebreaker: Tiebreaker,
+
+    /// Custom title bar on a frameless window, or the system's own
+    #[serde(default)]
+    pub window_chrome: WindowChrome,
+
+    /// Hide the launcher when it loses focus
+    #[serde(default)]
+    pub hide_on_focus_loss: bool,
+
+    /// Keep the window above other windows (toggled with F9 or the title bar pin)
+    #[serde(default)]
+    pub always_on_top: bool,
+
+    /// Let the search bar's icon and padding move the window too
+    #[serde(default)]
+    pub draggable_search_bar: bool,
+
+    /// What happens to the window and query after a successful launch
+    #[serde(default)]
+    pub after_launch: AfterLaunch,
+
+    /// How executables are started
+    #[serde(default)]
+    pub launch_method: LaunchMethod,
+
+    /// Programs that open files of an extension (without the dot) instead of
+    /// their association, e.g. "txt" → Notepad++; set on entries while indexing
+    #[serde(default)]
+    pub open_with: BTreeMap<String, String>,
+
+    /// Signal besides the error banner when a launch fails
+    #[serde(default)]
+    pub launch_feedback: LaunchFeedback,
+
+    /// Spacing and icon size of result rows
+    #[serde(default)]
+    pub density: Density,
+
+    /// Show the magnifier icon in the search bar
+    #[serde(default = "default_show_search_icon")]
+    pub show_search_icon: bool,
+
+    /// UI language code, e.g. "de" (default: the system language)
+    #[serde(default)]
+    pub language: Option<String>,
+
+    /// Hint shown in the empty search box instead of the default one
+    #[serde(default)]
+    pub search_placeholder: Option<String>,
+
+    /// Search URL offered as the last result, `{query}` replaced by the
+    /// typed text (default: no web search result)
+    #[serde(default)]
+    pub web_search_url: Option<String>,
+
+    /// Show each result's file extension next to its name
+    #[serde(default)]
+    pub show_extension: bool,
+
+    /// Trailing path components shown under each result, after a leading
+    /// "…" (0 = the whole path)
+    #[serde(default = "default_path_breadcrumb_depth")]
+    pub path_breadcrumb_depth: usize,
+
+    /// Mark programs added within this many days with a "new" badge (0 = off)
+    #[serde(default)]
+    pub highlight_new_days: u64,
+}
+
+/// An extra directory to index, either a bare path or a path with its own depth
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum IndexPath {
+    Path(String),
+    WithDepth { path: String, depth: usize },
+}
+
+impl IndexPath {
+    pub fn path(&self) -> &str {
+        match self {
+            IndexPath::Path(path) => path,
+            IndexPath::WithDepth { path, .. } => path,
+        }
+    }
+
+    /// Explicit depth for this path, if one was given
+    pub fn depth(&self) -> Option<usize> {
+        match self {
+            IndexPath::Path(_) => None,
+            IndexPath::WithDepth { depth, .. } => Some(*depth),
+        }
+    }
+}
+
+/// What the launcher does after successfully launching a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AfterLaunch {
+    /// Hide the window and reset the query
+    Hide,
+    /// Empty the query but stay open
+    #[default]
+    Clear,
+    /// Leave everything as it is
+    Keep,
+}
+
+impl FromStr for AfterLaunch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hide" => Ok(AfterLaunch::Hide),
+            "clear" => Ok(AfterLaunch::Clear),
+            "keep" => Ok(AfterLaunch::Keep),
+            _ => Err("expected \"hide\", \"clear\" or \"keep\"".to_string()),
+        }
+    }
+}
+
+/// When to rescan at startup if the index was loaded from cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReindexOnStartup {
+    /// Every start
+    #[default]
+    Always,
+    /// Only when the cache is older than `reindex_stale_hours`
+    Stale,
+    /// Never; reindex by hand (F5 or `--reindex`)
+    Never,
+}
+
+impl FromStr for ReindexOnStartup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "always" => Ok(ReindexOnStartup::Always),
+            "stale" => Ok(ReindexOnStartup::Stale),
+            "never" => Ok(ReindexOnStartup::Never),
+            _ => Err("expected \"always\", \"stale\" or \"never\"".to_string()),
+        }
+    }
+}
+
+/// How tightly result rows are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Density {
+    /// Roomy rows with `program_icon_size` icons
+    #[default]
+    Comfortable,
+    /// Tighter rows with smaller icons, fitting more results
+    Compact,
+}
+
+impl FromStr for Density {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "comfortable" => Ok(Density::Comfortable),
+            "compact" => Ok(Density::Compact),
+            _ => Err("expected \"comfortable\" or \"compact\"".to_string()),
+        }
+    }
+}
+
+/// Window frame and title bar style
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowChrome {
+    /// No system caption; LockSearch draws its own title bar
+    Frameless,
+    /// The system title bar and borders
+    Native,
+}
+
+impl Default for WindowChrome {
+    /// Frameless where the caption can be stripped while keeping resize borders
+    fn default() -> Self {
+        if cfg!(target_os = "windows") {
+            WindowChrome::Frameless
+        } else {
+            WindowChrome::Native
+        }
+    }
+}
+
+impl FromStr for WindowChrome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "frameless" => Ok(WindowChrome::Frameless),
+            "native" => Ok(WindowChrome::Native),
+            _ => Err("expected \"frameless\" or \"native\"".to_string()),
+        }
+    }
+}
+
+/// How the fuzzy matcher treats letter case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseSensitivity {
+    /// Case never matters
+    #[default]
+    Insensitive,
+    /// Case always matters
+    Sensitive,
+    /// Case matters only when the query contains an uppercase letter
+    Smart,
+}
+
+impl FromStr for CaseSensitivity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "insensitive" => Ok(CaseSensitivity::Insensitive),
+            "sensitive" => Ok(CaseSensitivity::Sensitive),
+            "smart" => Ok(CaseSensitivity::Smart),
+            _ => Err("expected \"insensitive\", \"sensitive\" or \"smart\"".to_string()),
+        }
+    }
+}
+
+/// Order of search results that score the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tiebreaker {
+    /// Most launched first, then alphabetical
+    #[default]
+    Usage,
+    /// Alphabetical, then shortest name first
+    Alphabetical,
+    /// Shortest name first, then alphabetical
+    Shortest,
+}
+
+impl FromStr for Tiebreaker {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "usage" => Ok(Tiebreaker::Usage),
+            "alphabetical" => Ok(Tiebreaker::Alphabetical),
+            "shortest" => Ok(Tiebreaker::Shortest),
+            _ => Err("expected \"usage\", \"alphabetical\" or \"shortest\"".to_string()),
+        }
+    }
+}
+
+/// How an executable result is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchMethod {
+    /// Through the shell file association, like double-clicking it
+    #[default]
+    Shell,
+    /// As a child process with the program's folder as working directory
+    Direct,
+}
+
+impl FromStr for LaunchMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "shell" => Ok(LaunchMethod::Shell),
+            "direct" => Ok(LaunchMethod::Direct),
+            _ => Err("expected \"shell\" or \"direct\"".to_string()),
+        }
+    }
+}
+
+/// How a failed launch is signalled, besides the error banner, for users
+/// not looking at the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchFeedback {
+    /// The banner only
+    None,
+    /// Flash the window border
+    #[default]
+    Visual,
+    /// Play the system error sound
+    Sound,
+    /// Flash and play the sound
+    Both,
+}
+
+impl LaunchFeedback {
+    pub fn flashes(self) -> bool {
+        matches!(self, LaunchFeedback::Visual | LaunchFeedback::Both)
+    }
+
+    pub fn beeps(self) -> bool {
+        matches!(self, LaunchFeedback::Sound | LaunchFeedback::Both)
+    }
+}
+
+impl FromStr for LaunchFeedback {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(LaunchFeedback::None),
+            "visual" => Ok(LaunchFeedback::Visual),
+            "sound" => Ok(LaunchFeedback::Sound),
+            "both" => Ok(LaunchFeedback::Both),
+            _ => Err("expected \"none\", \"visual\", \"sound\" or \"both\"".to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,26 +516,94 @@ fn default_window_height() -> f32 { 500.0 }
 fn default_search_icon_size() -> u16 { 18 }
 fn default_program_icon_size() -> u16 { 42 }
 fn default_max_results() -> usize { 10 }
+fn default_min_query_length() -> usize { 1 }
 fn default_bg_color() -> String { "#1B1F28".to_string() }
 fn default_panel_color() -> String { "#222733".to_string() }
 fn default_accent_color() -> String { "#7A5CCB".to_string() }
 fn default_selected_color() -> String { "#2E3546".to_string() }
+fn default_start_menu_depth() -> usize { 5 }
+fn default_program_files_depth() -> usize { 2 }
+fn default_index_start_menu() -> bool { true }
+fn default_index_program_files() -> bool { true }
+fn default_index_desktop() -> bool { true }
+fn default_icon_extraction_concurrency() -> usize { 4 }
+fn default_indexing_progress_ms() -> u64 { 100 }
+fn default_respect_dpi() -> bool { true }
+fn default_max_icon_cache_mb() -> u64 { 50 }
+fn default_path_breadcrumb_depth() -> usize { 3 }
+fn default_program_files_name_blocklist() -> Vec<String> {
+    ["crashpad", "helper", "service", "daemon", "notification"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
+fn default_blocklist_patterns() -> Vec<String> {
+    ["uninstall", "uninst", "unins", "update", "updater", "setup"]
+        .iter()
+        .map(|p| p.to_string())
+        .collect()
+}
 fn default_initial_sort() -> String { "alphabetical".to_string() }
 fn default_enable_cache() -> bool { true }
+fn default_reindex_stale_hours() -> u64 { 24 }
+fn default_show_search_icon() -> bool { true }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             window_width: default_window_width(),
             window_height: default_window_height(),
+            respect_dpi: default_respect_dpi(),
             search_icon_size: default_search_icon_size(),
             program_icon_size: default_program_icon_size(),
             max_results: default_max_results(),
+            min_score: 0,
+            min_query_length: default_min_query_length(),
             theme: ThemeConfig::default(),
             extra_index_paths: Vec::new(),
+            start_menu_depth: default_start_menu_depth(),
+            program_files_depth: default_program_files_depth(),
+            index_start_menu: default_index_start_menu(),
+            index_program_files: default_index_program_files(),
+            index_desktop: default_index_desktop(),
+            index_downloads: false,
+            blocklist_patterns: default_blocklist_patterns(),
+            program_files_name_blocklist: default_program_files_name_blocklist(),
+            follow_links: false,
+            icon_extraction_concurrency: default_icon_extraction_concurrency(),
+            max_icon_cache_mb: default_max_icon_cache_mb(),
+            indexing_progress_ms: default_indexing_progress_ms(),
             exclude_paths: Vec::new(),
+            exclude_extensions: Vec::new(),
+            empty_query_sources: Vec::new(),
             initial_sort: default_initial_sort(),
+            path_navigation: false,
+            path_component_matching: false,
             enable_cache: default_enable_cache(),
+            reindex_on_startup: ReindexOnStartup::default(),
+            reindex_stale_hours: default_reindex_stale_hours(),
+            cache_dir: None,
+            group_by_source: false,
+            pinned: Vec::new(),
+            hidden_programs: Vec::new(),
+            hide_on_focus_loss: false,
+            always_on_top: false,
+            draggable_search_bar: false,
+            after_launch: AfterLaunch::default(),
+            launch_method: LaunchMethod::default(),
+            open_with: BTreeMap::new(),
+            launch_feedback: LaunchFeedback::default(),
+            tiebreaker: Tiebreaker::default(),
+            case_sensitivity: CaseSensitivity::default(),
+            window_chrome: WindowChrome::default(),
+            show_search_icon: default_show_search_icon(),
+            density: Density::default(),
+            search_placeholder: None,
+            web_search_url: None,
+            language: None,
+            show_extension: false,
+            path_breadcrumb_depth: default_path_breadcrumb_depth(),
+            highlight_new_days: 0,
         }
     }
 }
@@ -107,48 +620,591 @@ impl Default for ThemeConfig {
 }
 
 impl Config {
-    /// Get the config file path (in project folder or next to executable)
+    /// Get the config file path (in project folder or next to executable).
+    /// YAML, TOML and JSON files are recognized, see `CONFIG_FILE_NAMES`.
     pub fn config_path() -> PathBuf {
         // Try current directory first
-        let local_path = PathBuf::from("config.yaml");
-        if local_path.exists() {
-            return local_path;
+        if let Some(local_config) = find_config(Path::new("")) {
+            return local_config;
         }
         
         // Try next to executable
         if let Ok(exe_path) = std::env::current_exe() {
             if let Some(exe_dir) = exe_path.parent() {
-                let exe_config = exe_dir.join("config.yaml");
-                if exe_config.exists() {
+                if let Some(exe_config) = find_config(exe_dir) {
                     return exe_config;
                 }
             }
         }
         
-        // Default to local path
-        local_path
+        // Default to local YAML
+        PathBuf::from(CONFIG_FILE_NAMES[0])
     }
 
-    /// Load config from file, or use defaults if not exists
+    /// Folder containing the config file
+    pub fn config_dir() -> PathBuf {
+        match Self::config_path().parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            // A bare file name lives in the current directory
+            _ => PathBuf::from("."),
+        }
+    }
+
+    /// Configured cache folder, with relative paths resolved against the config folder
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        let dir = PathBuf::from(self.cache_dir.as_deref()?.trim());
+        if dir.as_os_str().is_empty() {
+            None
+        } else if dir.is_relative() {
+            Some(Self::config_dir().join(dir))
+        } else {
+            Some(dir)
+        }
+    }
+
+    /// Load config from file (or defaults), then apply environment overrides
     pub fn load() -> Self {
+        let mut config = Self::load_file();
+        config.apply_env_overrides();
+        config.clamp_values();
+        config
+    }
+
+    /// Pull sizes and counts into usable ranges, so a hand-edited value can't
+    /// produce an invisible window or an empty result list
+    fn clamp_values(&mut self) {
+        clamp_field("window_width", &mut self.window_width, MIN_WINDOW_WIDTH, MAX_WINDOW_WIDTH);
+        clamp_field("window_height", &mut self.window_height, MIN_WINDOW_HEIGHT, MAX_WINDOW_HEIGHT);
+        clamp_field("search_icon_size", &mut self.search_icon_size, 8, 64);
+        clamp_field("program_icon_size", &mut self.program_icon_size, 16, 128);
+        clamp_field("max_results", &mut self.max_results, 1, MAX_RESULTS_LIMIT);
+        clamp_field("indexing_progress_ms", &mut self.indexing_progress_ms, 16, 5000);
+    }
+
+    /// Load config from file, or use defaults if not exists
+    fn load_file() -> Self {
         let path = Self::config_path();
         
         if path.exists() {
             match fs::read_to_string(&path) {
                 Ok(content) => {
-                    match serde_yaml::from_str(&content) {
-                        Ok(config) => return config,
+                    match parse(&path, &content) {
+                        Ok(config) => {
+                            log::info!("Loaded config from {}", path.display());
+                            return config;
+                        }
                         Err(e) => {
-                            eprintln!("Failed to parse config: {}", e);
+                            log::warn!("Failed to parse config {}: {}", path.display(), e);
                         }
                     }
                 }
                 Err(e) => {
-                    eprintln!("Failed to read config: {}", e);
+                    log::warn!("Failed to read config {}: {}", path.display(), e);
                 }
             }
         }
         
         Config::default()
     }
+
+    /// Apply `edit` to the config file on disk. Only the lines of the settings
+    /// that changed are rewritten, so comments and layout survive; JSON files,
+    /// and YAML or TOML the edit can't be made in, are written out whole with
+    /// `save`. Environment overrides aren't written.
+    /// Fails rather than overwrite a file that doesn't parse.
+    pub fn update_file(edit: impl FnOnce(&mut Config)) -> Result<(), String> {
+        let path = Self::config_path();
+        if !path.exists() {
+            let mut config = Config::default();
+            edit(&mut config);
+            return config.save();
+        }
+
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        let mut config = parse(&path, &content)?;
+        let before = to_mapping(&config)?;
+        edit(&mut config);
+        let after = to_mapping(&config)?;
+        match update_text(&path, &content, &before, &after) {
+            Some(text) => write_config(&path, &text),
+            None => config.save(),
+        }
+    }
+
+    /// Write this config to `config_path()` in the format of its extension.
+    /// Keys of the existing file this version doesn't know (e.g. from a newer
+    /// release) are kept. The text goes to a temporary file first, which then
+    /// replaces the old one, so a crash can't leave a half-written config.
+    /// Environment overrides in `self` are written too; use `update_file`
+    /// to change single settings of a loaded config.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::config_path();
+        let existing = match fs::read_to_string(&path) {
+            Ok(content) => Some(parse_value(&path, &content)?),
+            Err(_) => None,
+        };
+
+        let known = to_mapping(self)?;
+        // Unset options are left out; TOML has no null
+        let mut merged = serde_yaml::Mapping::new();
+        for (key, value) in &known {
+            if !value.is_null() {
+                merged.insert(key.clone(), value.clone());
+            }
+        }
+        if let Some(serde_yaml::Value::Mapping(existing)) = existing {
+            for (key, value) in existing {
+                if !known.contains_key(&key) {
+                    merged.insert(key, value);
+                }
+            }
+        }
+
+        let text = serialize(&path, &merged)?;
+        write_config(&path, &text)
+    }
+
+    /// Override scalar settings from `LOCKSEARCH_<FIELD>` environment variables,
+    /// e.g. `LOCKSEARCH_MAX_RESULTS=20` or `LOCKSEARCH_CACHE_DIR=D:\cache`
+    fn apply_env_overrides(&mut self) {
+        override_from_env("window_width", &mut self.window_width);
+        override_from_env("window_height", &mut self.window_height);
+        override_from_env("respect_dpi", &mut self.respect_dpi);
+        override_from_env("search_icon_size", &mut self.search_icon_size);
+        override_from_env("program_icon_size", &mut self.program_icon_size);
+        override_from_env("max_results", &mut self.max_results);
+        override_from_env("min_score", &mut self.min_score);
+        override_from_env("min_query_length", &mut self.min_query_length);
+        override_from_env("start_menu_depth", &mut self.start_menu_depth);
+        override_from_env("index_start_menu", &mut self.index_start_menu);
+        override_from_env("index_program_files", &mut self.index_program_files);
+        override_from_env("index_desktop", &mut self.index_desktop);
+        override_from_env("index_downloads", &mut self.index_downloads);
+        override_from_env("program_files_depth", &mut self.program_files_depth);
+        override_from_env("initial_sort", &mut self.initial_sort);
+        override_from_env("path_navigation", &mut self.path_navigation);
+        override_from_env("path_component_matching", &mut self.path_component_matching);
+        override_from_env("follow_links", &mut self.follow_links);
+        override_from_env("icon_extraction_concurrency", &mut self.icon_extraction_concurrency);
+        override_from_env("max_icon_cache_mb", &mut self.max_icon_cache_mb);
+        override_from_env("indexing_progress_ms", &mut self.indexing_progress_ms);
+        override_from_env("enable_cache", &mut self.enable_cache);
+        override_from_env("reindex_on_startup", &mut self.reindex_on_startup);
+        override_from_env("reindex_stale_hours", &mut self.reindex_stale_hours);
+        override_from_env("group_by_source", &mut self.group_by_source);
+        override_from_env("hide_on_focus_loss", &mut self.hide_on_focus_loss);
+        override_from_env("always_on_top", &mut self.always_on_top);
+        override_from_env("draggable_search_bar", &mut self.draggable_search_bar);
+        override_from_env("after_launch", &mut self.after_launch);
+        override_from_env("launch_method", &mut self.launch_method);
+        override_from_env("launch_feedback", &mut self.launch_feedback);
+        override_from_env("tiebreaker", &mut self.tiebreaker);
+        override_from_env("case_sensitivity", &mut self.case_sensitivity);
+        override_from_env("window_chrome", &mut self.window_chrome);
+        override_from_env("show_search_icon", &mut self.show_search_icon);
+        override_from_env("density", &mut self.density);
+        override_from_env("show_extension", &mut self.show_extension);
+        override_from_env("path_breadcrumb_depth", &mut self.path_breadcrumb_depth);
+        override_from_env("highlight_new_days", &mut self.highlight_new_days);
+
+        let mut cache_dir = self.cache_dir.clone().unwrap_or_default();
+        override_from_env("cache_dir", &mut cache_dir);
+        self.cache_dir = Some(cache_dir).filter(|dir| !dir.is_empty());
+
+        let mut placeholder = self.search_placeholder.clone().unwrap_or_default();
+        override_from_env("search_placeholder", &mut placeholder);
+        self.search_placeholder = Some(placeholder).filter(|p| !p.is_empty());
+
+        let mut web_search_url = self.web_search_url.clone().unwrap_or_default();
+        override_from_env("web_search_url", &mut web_search_url);
+        self.web_search_url = Some(web_search_url).filter(|url| !url.is_empty());
+
+        let mut language = self.language.clone().unwrap_or_default();
+        override_from_env("language", &mut language);
+        self.language = Some(language).filter(|l| !l.is_empty());
+    }
+}
+
+/// Clamp a config value into `min..=max`, logging when it is corrected.
+/// Values that don't compare (NaN) become `min`.
+fn clamp_field<T: PartialOrd + Copy + fmt::Display>(field: &str, value: &mut T, min: T, max: T) {
+    if *value >= min && *value <= max {
+        return;
+    }
+    let clamped = if *value > max { max } else { min };
+    log::warn!("{} = {} is out of range ({} to {}); using {}", field, value, min, max, clamped);
+    *value = clamped;
+}
+
+/// First config file present in `dir`
+fn find_config(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
+}
+
+/// Serialize config text with the format matching the file extension
+fn serialize<T: Serialize>(path: &Path, config: &T) -> Result<String, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::to_string_pretty(config).map_err(|e| e.to_string()),
+        Some("json") => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+        _ => serde_yaml::to_string(config).map_err(|e| e.to_string()),
+    }
+}
+
+/// Deserialize config text with the format matching the file extension
+fn parse(path: &Path, content: &str) -> Result<Config, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(content).map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str(content).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+    }
+}
+
+/// Config text as a generic value, keeping keys `Config` doesn't have
+fn parse_value(path: &Path, content: &str) -> Result<serde_yaml::Value, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(content).map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str(content).map_err(|e| e.to_string()),
+        _ => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+    }
+}
+
+/// Every setting of `config` by key, unset options as null
+fn to_mapping(config: &Config) -> Result<serde_yaml::Mapping, String> {
+    match serde_yaml::to_value(config).map_err(|e| e.to_string())? {
+        serde_yaml::Value::Mapping(mapping) => Ok(mapping),
+        _ => Err("config did not serialize to a mapping".to_string()),
+    }
+}
+
+/// Write config text through a temporary file that then replaces `path`,
+/// so a crash can't leave a half-written config
+fn write_config(path: &Path, text: &str) -> Result<(), String> {
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp_path = path.with_file_name(format!("{}.tmp", file_name));
+    fs::write(&temp_path, text).map_err(|e| e.to_string())?;
+    if let Err(e) = fs::rename(&temp_path, path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e.to_string());
+    }
+    log::info!("Saved config to {}", path.display());
+    Ok(())
+}
+
+/// Config formats whose text `update_text` can edit line by line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextFormat {
+    Yaml,
+    Toml,
+}
+
+/// `content` with only the top-level keys that differ between `before` and
+/// `after` rewritten, each on a single line. None for JSON, or when the
+/// edited text doesn't read back as `content` with those keys changed.
+fn update_text(
+    path: &Path,
+    content: &str,
+    before: &serde_yaml::Mapping,
+    after: &serde_yaml::Mapping,
+) -> Option<String> {
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => TextFormat::Toml,
+        Some("json") => return None,
+        _ => TextFormat::Yaml,
+    };
+    let serde_yaml::Value::Mapping(mut expected) = parse_value(path, content).ok()? else {
+        return None;
+    };
+
+    let mut text = content.to_string();
+    for (key, value) in after {
+        if before.get(key) == Some(value) {
+            continue;
+        }
+        text = replace_key(format, &text, key.as_str()?, value)?;
+        if value.is_null() {
+            expected.remove(key);
+        } else {
+            expected.insert(key.clone(), value.clone());
+        }
+    }
+
+    match parse_value(path, &text).ok()? {
+        serde_yaml::Value::Mapping(written) if written == expected => Some(text),
+        _ => None,
+    }
+}
+
+/// `text` with the line of the top-level `key` (and the lines its value
+/// continues on) replaced by `key` set to `value` in flow style. A missing key
+/// is added at the end of the top-level keys; a null value removes the key.
+fn replace_key(format: TextFormat, text: &str, key: &str, value: &serde_yaml::Value) -> Option<String> {
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let line = match value {
+        serde_yaml::Value::Null => None,
+        // JSON is valid flow-style YAML
+        _ if format == TextFormat::Yaml => Some(format!("{}: {}", key, serde_json::to_string(value).ok()?)),
+        _ => Some(format!("{} = {}", key, toml::Value::try_from(value).ok()?)),
+    };
+
+    let mut lines: Vec<&str> = text.split_inclusive('\n').collect();
+    // TOML keys after the first [table] header belong to that table
+    let top_level_end = match format {
+        TextFormat::Yaml => lines.len(),
+        TextFormat::Toml => lines.iter().position(|l| l.starts_with('[')).unwrap_or(lines.len()),
+    };
+    let new_line = line.map(|line| line + newline);
+
+    let Some(start) = lines[..top_level_end].iter().position(|l| is_key_line(format, l, key)) else {
+        let Some(new_line) = new_line else {
+            return Some(text.to_string());
+        };
+        // Comments right above a table header belong to the table
+        let mut insert_at = top_level_end;
+        if format == TextFormat::Toml {
+            while insert_at > 0 && matches!(lines[insert_at - 1].trim().chars().next(), None | Some('#')) {
+                insert_at -= 1;
+            }
+        }
+        let mut before: String = lines[..insert_at].concat();
+        if !before.is_empty() && !before.ends_with('\n') {
+            before.push_str(newline);
+        }
+        return Some(before + &new_line + &lines[insert_at..].concat());
+    };
+
+    // Indented lines (and YAML block list items) continue the value; blank
+    // lines only when more of it follows
+    let mut end = start + 1;
+    for (i, l) in lines.iter().enumerate().skip(start + 1) {
+        let continues = l.starts_with([' ', '\t'])
+            || match format {
+                TextFormat::Yaml => l.starts_with("- ") || l.trim_end() == "-",
+                TextFormat::Toml => l.starts_with(']'),
+            };
+        if continues {
+            end = i + 1;
+        } else if !l.trim().is_empty() {
+            break;
+        }
+    }
+
+    let replacement = new_line.unwrap_or_default();
+    let mut edited: String = lines[..start].concat();
+    edited.push_str(&replacement);
+    lines.drain(..end);
+    edited.push_str(&lines.concat());
+    Some(edited)
+}
+
+/// Whether `line` sets the top-level `key`
+fn is_key_line(format: TextFormat, line: &str, key: &str) -> bool {
+    let Some(rest) = line.strip_prefix(key) else {
+        return false;
+    };
+    match format {
+        TextFormat::Yaml => rest.starts_with(':'),
+        TextFormat::Toml => rest.trim_start().starts_with('='),
+    }
+}
+
+/// Replace `target` with the parsed value of `LOCKSEARCH_<FIELD>`, if set.
+/// Values that don't parse are ignored with a warning.
+fn override_from_env<T>(field: &str, target: &mut T)
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let name = format!("{}{}", ENV_PREFIX, field.to_uppercase());
+    let Ok(value) = std::env::var(&name) else {
+        return;
+    };
+    match value.trim().parse() {
+        Ok(parsed) => {
+            log::info!("Using {} from the environment", name);
+            *target = parsed;
+        }
+        Err(e) => log::warn!("Ignoring invalid {}={:?}: {}", name, value, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mappings(edit: impl FnOnce(&mut Config)) -> (serde_yaml::Mapping, serde_yaml::Mapping) {
+        let mut config = Config::default();
+        let before = to_mapping(&config).unwrap();
+        edit(&mut config);
+        (before, to_mapping(&config).unwrap())
+    }
+
+    #[test]
+    fn yaml_edits_keep_comments_and_other_keys() {
+        let content = "# Launcher settings\nmax_results: 10\n\n# Pinned programs\npinned:\n  - Old\n\n# On top\nalways_on_top: false # stays\n";
+        let (before, after) = mappings(|config| config.pinned = vec!["Firefox".to_string(), "Code".to_string()]);
+        let text = update_text(Path::new("config.yaml"), content, &before, &after).unwrap();
+        assert_eq!(
+            text,
+            "# Launcher settings\nmax_results: 10\n\n# Pinned programs\npinned: [\"Firefox\",\"Code\"]\n\n# On top\nalways_on_top: false # stays\n"
+        );
+    }
+
+    #[test]
+    fn missing_yaml_keys_are_added_at_the_end() {
+        let content = "# Only a comment\nmax_results: 10";
+        let (before, after) = mappings(|config| config.always_on_top = true);
+        let text = update_text(Path::new("config.yaml"), content, &before, &after).unwrap();
+        assert_eq!(text, "# Only a comment\nmax_results: 10\nalways_on_top: true\n");
+    }
+
+    #[test]
+    fn toml_edits_stay_above_the_first_table() {
+        let content = "# Settings\nhidden_programs = [\n    \"Old\",\n]\n\n# Colors\n[theme]\naccent = \"#FFFFFF\"\n";
+        let (before, after) = mappings(|config| {
+            config.hidden_programs = vec!["Paint".to_string()];
+            config.always_on_top = true;
+        });
+        let text = update_text(Path::new("config.toml"), content, &before, &after).unwrap();
+        assert_eq!(
+            text,
+            "# Settings\nhidden_programs = [\"Paint\"]\nalways_on_top = true\n\n# Colors\n[theme]\naccent = \"#FFFFFF\"\n"
+        );
+    }
+
+    #[test]
+    fn json_is_written_out_whole() {
+        let (before, after) = mappings(|config| config.always_on_top = true);
+        assert_eq!(update_text(Path::new("config.json"), "{}", &before, &after), None);
+    }
+
+    /// `content` parsed as YAML, then overridden from the environment.
+    /// Each test sets its own variable, as the environment is shared.
+    fn overridden(content: &str) -> Config {
+        let mut config = parse(Path::new("config.yaml"), content).unwrap();
+        config.apply_env_overrides();
+        config
+    }
+
+    #[test]
+    fn environment_variables_beat_the_file() {
+        std::env::set_var("LOCKSEARCH_MAX_RESULTS", "20");
+        let config = overridden("max_results: 10\n");
+        std::env::remove_var("LOCKSEARCH_MAX_RESULTS");
+        assert_eq!(config.max_results, 20);
+    }
+
+    #[test]
+    fn environment_paths_beat_the_file() {
+        std::env::set_var("LOCKSEARCH_CACHE_DIR", "D:\\locksearch");
+        let config = overridden("cache_dir: \"cache\"\n");
+        std::env::remove_var("LOCKSEARCH_CACHE_DIR");
+        assert_eq!(config.cache_dir.as_deref(), Some("D:\\locksearch"));
+    }
+
+    #[test]
+    fn invalid_environment_values_are_ignored() {
+        std::env::set_var("LOCKSEARCH_WINDOW_WIDTH", "wide");
+        let config = overridden("window_width: 800.0\n");
+        std::env::remove_var("LOCKSEARCH_WINDOW_WIDTH");
+        assert_eq!(config.window_width, 800.0);
+    }
+
+    #[test]
+    fn every_format_reads_the_same_settings() {
+        let yaml = r##"
+max_results: 25
+after_launch: "hide"
+language: "de"
+pinned: ["Firefox", "Code"]
+extra_index_paths:
+  - "D:\\Tools"
+  - { path: "D:\\Games", depth: 4 }
+theme:
+  accent: "#FF0000"
+open_with:
+  txt: "C:\\Tools\\notepad++.exe"
+"##;
+        let toml = r##"
+max_results = 25
+after_launch = "hide"
+language = "de"
+pinned = ["Firefox", "Code"]
+extra_index_paths = ["D:\\Tools", { path = "D:\\Games", depth = 4 }]
+
+[theme]
+accent = "#FF0000"
+
+[open_with]
+txt = "C:\\Tools\\notepad++.exe"
+"##;
+        let json = r##"{
+  "max_results": 25,
+  "after_launch": "hide",
+  "language": "de",
+  "pinned": ["Firefox", "Code"],
+  "extra_index_paths": ["D:\\Tools", { "path": "D:\\Games", "depth": 4 }],
+  "theme": { "accent": "#FF0000" },
+  "open_with": { "txt": "C:\\Tools\\notepad++.exe" }
+}"##;
+        let from_yaml = parse(Path::new("config.yaml"), yaml).unwrap();
+        assert_eq!(from_yaml.max_results, 25);
+        assert_eq!(from_yaml.after_launch, AfterLaunch::Hide);
+        assert_eq!(from_yaml.extra_index_paths[1].path(), "D:\\Games");
+        assert_eq!(from_yaml.theme.accent, "#FF0000");
+
+        let expected = to_mapping(&from_yaml).unwrap();
+        assert_eq!(to_mapping(&parse(Path::new("config.toml"), toml).unwrap()).unwrap(), expected);
+        assert_eq!(to_mapping(&parse(Path::new("config.json"), json).unwrap()).unwrap(), expected);
+    }
+
+    #[test]
+    fn yaml_wins_over_toml_which_wins_over_json() {
+        let dir = std::env::temp_dir().join(format!("locksearch-find-config-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        assert_eq!(find_config(&dir), None);
+        fs::write(dir.join("config.json"), "{}").unwrap();
+        assert_eq!(find_config(&dir), Some(dir.join("config.json")));
+        fs::write(dir.join("config.toml"), "").unwrap();
+        assert_eq!(find_config(&dir), Some(dir.join("config.toml")));
+        fs::write(dir.join("config.yaml"), "").unwrap();
+        assert_eq!(find_config(&dir), Some(dir.join("config.yaml")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn out_of_range_values_are_clamped_to_their_bounds() {
+        let mut low = Config {
+            window_width: 10.0,
+            window_height: -1.0,
+            search_icon_size: 0,
+            program_icon_size: 1,
+            max_results: 0,
+            ..Config::default()
+        };
+        low.clamp_values();
+        assert_eq!((low.window_width, low.window_height), (MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT));
+        assert_eq!((low.search_icon_size, low.program_icon_size, low.max_results), (8, 16, 1));
+
+        let mut high = Config {
+            window_width: 100_000.0,
+            window_height: f32::NAN,
+            search_icon_size: 500,
+            program_icon_size: 500,
+            max_results: usize::MAX,
+            ..Config::default()
+        };
+        high.clamp_values();
+        assert_eq!((high.window_width, high.window_height), (MAX_WINDOW_WIDTH, MIN_WINDOW_HEIGHT));
+        assert_eq!((high.search_icon_size, high.program_icon_size, high.max_results), (64, 128, MAX_RESULTS_LIMIT));
+
+        // Values in range are left alone
+        let mut fine = Config { window_width: 800.0, program_icon_size: 32, max_results: 12, ..Config::default() };
+        fine.clamp_values();
+        assert_eq!((fine.window_width, fine.program_icon_size, fine.max_results), (800.0, 32, 12));
+    }
 }
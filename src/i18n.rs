@@ -0,0 +1,209 @@
+//! Translatable UI strings. Each bundled language is a JSON file of the same
+//! keys as `locales/en.json`; keys a translation lacks fall back to English.
+
+use crate::indexer::ProgramSource;
+use crate::platform;
+use crate::ui::KeyAction;
+use serde::Deserialize;
+
+/// Language used when nothing else matches, and for missing keys
+const FALLBACK_LANGUAGE: &str = "en";
+
+/// Bundled translations keyed by language code
+const BUNDLED: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("de", include_str!("../locales/de.json")),
+];
+
+/// UI text in one language. `{count}` in a template is replaced by a number.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Strings {
+    pub no_results: String,
+    /// Shown in place of results while a first index run hasn't found anything yet
+    pub indexing_programs: String,
+    /// Search box hint unless `search_placeholder` is configured
+    pub search_placeholder: String,
+    pub launch: String,
+    pub run_as_admin: String,
+    pub run_as_user: String,
+    pub user_name_hint: String,
+    pub open_folder: String,
+    pub copy_path: String,
+    pub pin: String,
+    pub unpin: String,
+    pub hide_result: String,
+    launched_once: String,
+    launched_times: String,
+    show_more_one: String,
+    show_more: String,
+    pub new_badge: String,
+    pub not_found: String,
+    pub source: String,
+    pub target: String,
+    folder_not_indexed: String,
+    folders_not_indexed: String,
+    source_start_menu: String,
+    source_desktop: String,
+    source_program_files: String,
+    source_filesystem: String,
+    source_provider: String,
+    /// `{query}` is replaced by the search text
+    pub search_web: String,
+    /// Title of the F1 help
+    pub keyboard_shortcuts: String,
+    pub key_ctrl: String,
+    pub key_shift: String,
+    pub key_enter: String,
+    pub key_escape: String,
+    pub key_delete: String,
+    pub key_home: String,
+    pub key_end: String,
+    pub key_page_up: String,
+    pub key_page_down: String,
+    shortcut_next: String,
+    shortcut_previous: String,
+    shortcut_first: String,
+    shortcut_last: String,
+    shortcut_page_up: String,
+    shortcut_page_down: String,
+    shortcut_activate: String,
+    shortcut_reveal_folder: String,
+    shortcut_run_elevated: String,
+    shortcut_toggle_pin: String,
+    shortcut_toggle_stats: String,
+    shortcut_remove_entry: String,
+    shortcut_toggle_help: String,
+    shortcut_reindex: String,
+    shortcut_clear_icon_cache: String,
+    shortcut_open_config_folder: String,
+    shortcut_open_icon_cache_folder: String,
+    shortcut_toggle_always_on_top: String,
+    shortcut_escape: String,
+    pub gesture_click: String,
+    pub gesture_shift_click: String,
+    pub gesture_middle_click: String,
+    pub gesture_right_click: String,
+    pub help_click: String,
+    pub help_shift_click: String,
+    pub help_middle_click: String,
+    pub help_right_click: String,
+    /// Next to the `a:`, `s:`, `f:` and `w:` query operators
+    pub help_operators: String,
+}
+
+impl Strings {
+    /// Strings for `language` (e.g. "de", "de-AT", "de_DE.UTF-8"), or for the
+    /// OS display language when `None`. Unknown languages get English.
+    pub fn load(language: Option<&str>) -> Self {
+        let requested = language.map(str::to_string).or_else(platform::user_language);
+        let code = requested.as_deref().map(language_code).unwrap_or_default();
+
+        let mut merged = bundled(FALLBACK_LANGUAGE).expect("English strings are bundled and valid");
+        if code != FALLBACK_LANGUAGE {
+            match bundled(&code) {
+                Some(serde_json::Value::Object(translated)) => {
+                    if let serde_json::Value::Object(base) = &mut merged {
+                        // Only text replaces text, so a bad entry can't break loading
+                        base.extend(translated.into_iter().filter(|(_, value)| value.is_string()));
+                    }
+                }
+                _ => log::debug!("No bundled strings for language {:?}; using English", code),
+            }
+        }
+
+        serde_json::from_value(merged).expect("English strings cover every key")
+    }
+
+    /// "Launched 3 times"
+    pub fn launched(&self, count: usize) -> String {
+        fill(if count == 1 { &self.launched_once } else { &self.launched_times }, count)
+    }
+
+    /// "Show 10 more results"
+    pub fn show_more(&self, count: usize) -> String {
+        fill(if count == 1 { &self.show_more_one } else { &self.show_more }, count)
+    }
+
+    /// "2 folders not indexed"
+    pub fn folders_not_indexed(&self, count: usize) -> String {
+        fill(if count == 1 { &self.folder_not_indexed } else { &self.folders_not_indexed }, count)
+    }
+
+    /// Section header and tooltip name of a source
+    pub fn source_label(&self, source: &ProgramSource) -> &str {
+        match source {
+            ProgramSource::StartMenu => &self.source_start_menu,
+            ProgramSource::Desktop => &self.source_desktop,
+            ProgramSource::ProgramFiles => &self.source_program_files,
+            ProgramSource::Filesystem => &self.source_filesystem,
+            ProgramSource::Provider => &self.source_provider,
+        }
+    }
+
+    /// F1 help description of a key binding
+    pub fn key_action(&self, action: KeyAction) -> &str {
+        match action {
+            KeyAction::Next => &self.shortcut_next,
+            KeyAction::Previous => &self.shortcut_previous,
+            KeyAction::First => &self.shortcut_first,
+            KeyAction::Last => &self.shortcut_last,
+            KeyAction::PageUp => &self.shortcut_page_up,
+            KeyAction::PageDown => &self.shortcut_page_down,
+            KeyAction::Activate => &self.shortcut_activate,
+            KeyAction::RevealFolder => &self.shortcut_reveal_folder,
+            KeyAction::RunElevated => &self.shortcut_run_elevated,
+            KeyAction::TogglePin => &self.shortcut_toggle_pin,
+            KeyAction::ToggleStats => &self.shortcut_toggle_stats,
+            KeyAction::RemoveEntry => &self.shortcut_remove_entry,
+            KeyAction::ToggleHelp => &self.shortcut_toggle_help,
+            KeyAction::Reindex => &self.shortcut_reindex,
+            KeyAction::ClearIconCache => &self.shortcut_clear_icon_cache,
+            KeyAction::OpenConfigFolder => &self.shortcut_open_config_folder,
+            KeyAction::OpenIconCacheFolder => &self.shortcut_open_icon_cache_folder,
+            KeyAction::ToggleAlwaysOnTop => &self.shortcut_toggle_always_on_top,
+            KeyAction::Escape => &self.shortcut_escape,
+        }
+    }
+}
+
+/// Parsed JSON of a bundled language
+fn bundled(code: &str) -> Option<serde_json::Value> {
+    let (_, json) = BUNDLED.iter().find(|(lang, _)| *lang == code)?;
+    match serde_json::from_str(json) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("Ignoring invalid bundled strings for {:?}: {}", code, e);
+            None
+        }
+    }
+}
+
+/// Lowercased language part of a locale name: "de" for "de-AT" or "de_DE.UTF-8"
+fn language_code(locale: &str) -> String {
+    locale
+        .split(['-', '_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+fn fill(template: &str, count: usize) -> String {
+    template.replace("{count}", &count.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_of_one_use_the_singular() {
+        let english = Strings::load(Some("en"));
+        assert_eq!(english.show_more(1), "Show 1 more result");
+        assert_eq!(english.show_more(10), "Show 10 more results");
+        assert_eq!(english.launched(1), "Launched 1 time");
+        let german = Strings::load(Some("de-AT"));
+        assert_eq!(german.show_more(1), "1 weiteres Ergebnis anzeigen");
+        assert_eq!(german.show_more(3), "3 weitere Ergebnisse anzeigen");
+    }
+}
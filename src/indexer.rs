@@ -1,9 +1,19 @@
+use crate::config::Config;
+use crate::platform;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::io;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, watch, Mutex, RwLock};
 use walkdir::WalkDir;
 
 /// Represents a program/executable entry
@@ -12,269 +22,1246 @@ pub struct ProgramEntry {
     pub path: PathBuf,
     pub name: String,
     pub display_name: String,
+    /// `display_name` before a folder was added to tell it apart from a
+    /// namesake, e.g. "Python" for "Python (Python311)"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_name: Option<String>,
     pub source: ProgramSource,
     pub icon_path: Option<PathBuf>,
+    /// Resolved target of a shortcut, when it differs from `path`
+    #[serde(default)]
+    pub target: Option<PathBuf>,
+    /// Lowercased file stem of `target`, e.g. "chrome" for a "My Browser" shortcut
+    #[serde(default)]
+    pub target_name: Option<String>,
+    /// Unix time (seconds) the file was created, or last modified if unknown
+    #[serde(default)]
+    pub modified: Option<u64>,
+    /// Program to open this file with instead of its default association (`open_with`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opener: Option<PathBuf>,
+}
+
+/// Entries are identified by their path, compared case-insensitively as Windows does
+impl PartialEq for ProgramEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for ProgramEntry {}
+
+impl Hash for ProgramEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl ProgramEntry {
+    /// Stable identity of the entry across reindexing: its lowercased path
+    pub fn key(&self) -> String {
+        self.path.to_string_lossy().to_lowercase()
+    }
+
+    /// Lowercased extension of what actually runs: the shortcut target if known
+    pub fn extension(&self) -> Option<String> {
+        self.target
+            .as_deref()
+            .unwrap_or(&self.path)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+    }
+
+    /// Names pins and hides match: the display name, and the one it had
+    /// before being told apart from a namesake, which older configs name
+    pub fn config_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.display_name.as_str()).chain(self.base_name.as_deref())
+    }
 }
 
 /// Where the program was found
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ProgramSource {
     StartMenu,
+    /// Shortcuts on the Desktop, or in Downloads when enabled
+    Desktop,
     ProgramFiles,
+    /// Folder contents listed by path navigation; never indexed
+    Filesystem,
+    /// Results of a `ResultProvider`; never indexed
+    Provider,
 }
 
+impl ProgramSource {
+    /// Sort priority — lower values are listed first
+    pub fn priority(&self) -> u8 {
+        match self {
+            ProgramSource::StartMenu => 0,
+            ProgramSource::Desktop => 1,
+            ProgramSource::ProgramFiles => 2,
+            ProgramSource::Filesystem => 3,
+            ProgramSource::Provider => 4,
+        }
+    }
+
+    /// Section header shown when results are grouped by source
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProgramSource::StartMenu => "Applications",
+            ProgramSource::Desktop => "Desktop",
+            ProgramSource::ProgramFiles => "Program Files",
+            ProgramSource::Filesystem => "Files",
+            ProgramSource::Provider => "More results",
+        }
+    }
+}
+
+/// Indexing settings taken from the user's config
+#[derive(Clone, Debug)]
+pub struct IndexOptions {
+    pub start_menu_depth: usize,
+    pub program_files_depth: usize,
+    /// Extra directories to scan for executables, with their scan depth
+    pub extra_paths: Vec<(PathBuf, usize)>,
+    /// Lowercased names of installer/updater executables to skip
+    pub blocklist: Vec<String>,
+    /// Lowercased name parts of helper executables to skip
+    pub helper_blocklist: Vec<String>,
+    /// Lowercased extensions (without the dot) never indexed
+    pub excluded_extensions: Vec<String>,
+    /// Program opening files of a lowercased extension, see `ProgramEntry::opener`
+    pub openers: HashMap<String, PathBuf>,
+    /// Descend into symlinked/junctioned directories instead of skipping them
+    pub follow_links: bool,
+    pub index_start_menu: bool,
+    pub index_program_files: bool,
+    pub index_desktop: bool,
+    pub index_downloads: bool,
+    /// Worker threads extracting icons, at least 1
+    pub icon_concurrency: usize,
+    /// Least time between progress updates
+    pub progress_interval: Duration,
+    /// Size in bytes the icon cache is trimmed to after indexing, if limited
+    pub icon_cache_limit: Option<u64>,
+}
+
+impl IndexOptions {
+    pub fn from_config(config: &Config) -> Self {
+        // A depth of 0 would only visit the root directory itself
+        let program_files_depth = config.program_files_depth.max(1);
+        Self {
+            start_menu_depth: config.start_menu_depth.max(1),
+            program_files_depth,
+            extra_paths: config
+                .extra_index_paths
+                .iter()
+                .map(|p| {
+                    let depth = p.depth().unwrap_or(program_files_depth).max(1);
+                    (PathBuf::from(p.path()), depth)
+                })
+                .collect(),
+            blocklist: config
+                .blocklist_patterns
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+            helper_blocklist: config
+                .program_files_name_blocklist
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+            excluded_extensions: config
+                .exclude_extensions
+                .iter()
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            openers: config
+                .open_with
+                .iter()
+                .map(|(ext, program)| (ext.trim().trim_start_matches('.').to_lowercase(), PathBuf::from(program.trim())))
+                .filter(|(ext, program)| !ext.is_empty() && !program.as_os_str().is_empty())
+                .collect(),
+            follow_links: config.follow_links,
+            index_start_menu: config.index_start_menu,
+            index_program_files: config.index_program_files,
+            index_desktop: config.index_desktop,
+            index_downloads: config.index_downloads,
+            icon_concurrency: config.icon_extraction_concurrency.max(1),
+            progress_interval: Duration::from_millis(config.indexing_progress_ms),
+            icon_cache_limit: (config.max_icon_cache_mb > 0)
+                .then_some(config.max_icon_cache_mb.saturating_mul(1024 * 1024)),
+        }
+    }
+}
+
+impl Default for IndexOptions {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+/// Progress of a running index, pushed to listeners as it changes
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IndexProgress {
+    /// Entries found so far; on the final update, the entries of the finished
+    /// index (with duplicates merged)
+    pub found: usize,
+    /// Set on the final update of a run
+    pub done: bool,
+    /// The entries found so far were just published for searching. Only a
+    /// cold start (nothing loaded yet) streams entries before the run ends.
+    pub partial: bool,
+}
+
+/// An icon extracted after its entry was already published
+#[derive(Clone, Debug)]
+pub struct IconReady {
+    /// Path of the entry the icon belongs to
+    pub path: PathBuf,
+    pub icon_path: PathBuf,
+}
+
+/// Diagnostics gathered during the last indexing run
+#[derive(Clone, Debug, Default)]
+pub struct IndexStats {
+    /// Entries kept per source
+    pub per_source: BTreeMap<ProgramSource, usize>,
+    /// Files visited while walking the indexed directories
+    pub files_walked: usize,
+    /// Entries merged into another one that resolves to the same target
+    pub merged: usize,
+    /// Junctions and symlinks skipped instead of being walked into
+    pub reparse_points: usize,
+    /// Folders that could not be indexed at all
+    pub errors: Vec<IndexError>,
+    /// Wall-clock time of the last completed run
+    pub last_duration: Option<Duration>,
+}
+
+/// A folder that could not be indexed, e.g. missing or access denied
+#[derive(Clone, Debug)]
+pub struct IndexError {
+    pub path: PathBuf,
+    pub source: ProgramSource,
+    pub message: String,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.path.display(), self.source.label(), self.message)
+    }
+}
+
+/// Launch history of one program
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UsageStat {
+    /// Number of successful launches
+    pub launches: u32,
+    /// Unix time (seconds) of the most recent launch
+    pub last_launched: u64,
+}
+
+/// Launch history keyed by program path
+pub type UsageMap = HashMap<PathBuf, UsageStat>;
+
+/// How many queries remember their last chosen result
+const MAX_REMEMBERED_SELECTIONS: usize = 200;
+
+/// How many launched queries are kept for recall
+const MAX_QUERY_HISTORY: usize = 50;
+
+/// Icon notifications buffered for slow listeners; older ones are dropped
+const ICON_UPDATE_CAPACITY: usize = 256;
+
 /// The program index
 pub struct ProgramIndex {
-    entries: Arc<RwLock<Vec<ProgramEntry>>>,
-    is_indexing: Arc<RwLock<bool>>,
-    indexed_count: Arc<RwLock<usize>>,
+    /// Swapped wholesale on reindex so readers can hold a snapshot without copying
+    entries: Arc<RwLock<Arc<Vec<ProgramEntry>>>>,
+    /// Claimed with a compare-exchange, so only one run can ever start
+    is_indexing: Arc<AtomicBool>,
+    stats: Arc<RwLock<IndexStats>>,
+    /// Kept apart from `entries` so reindexing doesn't wipe it
+    usage: Arc<RwLock<UsageMap>>,
+    /// Result chosen for each recent normalized query, most recent last
+    selections: Arc<RwLock<Vec<(String, PathBuf)>>>,
+    /// Queries that led to a launch, as typed, most recent last
+    history: Arc<RwLock<Vec<String>>>,
+    /// Held from changing the entries, usage, selections or history until
+    /// the change is on disk, so snapshots are written in the order they were taken
+    state_writes: Arc<Mutex<()>>,
+    /// Announces icons extracted after indexing published the entries
+    icon_updates: broadcast::Sender<IconReady>,
+    /// Set when a run with no cached icons couldn't extract any; cleared
+    /// when the next run starts or the icon cache is cleared
+    icon_extraction_disabled: Arc<AtomicBool>,
+    /// False when no cache folder could be created; nothing is written to disk then
+    persistent: bool,
     icon_cache_dir: PathBuf,
+    /// When each cached icon was last used by the index, see `track_icon_use`
+    icon_access_path: PathBuf,
     cache_path: PathBuf,
+    usage_path: PathBuf,
+    selections_path: PathBuf,
+    history_path: PathBuf,
 }
 
 impl Default for ProgramIndex {
     fn default() -> Self {
-        Self::new()
+        Self::new(None)
     }
 }
 
 impl ProgramIndex {
-    pub fn new() -> Self {
-        // Create icon cache directory
-        let icon_cache_dir = dirs::cache_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("locksearch")
-            .join("icons");
-        let _ = fs::create_dir_all(&icon_cache_dir);
-
-        let cache_path = dirs::cache_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("locksearch")
-            .join("index_cache.json");
+    /// Create an index storing its cache in `cache_dir`, or when `None` in
+    /// the first usable of the user's cache folder, the executable's folder and
+    /// the temp folder. If no folder can be created the index runs in memory.
+    pub fn new(cache_dir: Option<PathBuf>) -> Self {
+        let candidates = match cache_dir {
+            Some(dir) => vec![dir],
+            None => default_cache_dirs(),
+        };
+
+        // Create icon cache directory (and the cache folder with it)
+        let usable = candidates.iter().find(|dir| match fs::create_dir_all(dir.join("icons")) {
+            Ok(()) => true,
+            Err(e) => {
+                log::warn!("Failed to create cache folder {}: {}", dir.display(), e);
+                false
+            }
+        });
+        match usable {
+            Some(dir) => Self::with_storage(Some(dir.as_path()), Vec::new()),
+            None => {
+                log::warn!("No usable cache folder; the index, icons and history won't be saved");
+                Self::in_memory()
+            }
+        }
+    }
+
+    /// An empty index that never touches the disk: nothing is loaded or
+    /// saved, and indexing extracts no icons
+    pub fn in_memory() -> Self {
+        Self::with_entries(Vec::new())
+    }
+
+    /// An in-memory index (see `in_memory`) holding `entries`, e.g. for
+    /// searching a known list without indexing
+    pub fn with_entries(entries: Vec<ProgramEntry>) -> Self {
+        Self::with_storage(None, entries)
+    }
+
+    /// Index keeping its state in `cache_dir`, or only in memory when `None`
+    fn with_storage(cache_dir: Option<&Path>, entries: Vec<ProgramEntry>) -> Self {
+        let persistent = cache_dir.is_some();
+        // Without a folder the paths stay empty and are never read or written
+        let file = |name: &str| cache_dir.map(|dir| dir.join(name)).unwrap_or_default();
+        fn load<T: DeserializeOwned + Default>(persistent: bool, path: &Path, what: &str) -> T {
+            if persistent { read_json(path, what) } else { T::default() }
+        }
+
+        let usage_path = file("usage.json");
+        let selections_path = file("selections.json");
+        let history_path = file("history.json");
 
         Self {
-            entries: Arc::new(RwLock::new(Vec::new())),
-            is_indexing: Arc::new(RwLock::new(false)),
-            indexed_count: Arc::new(RwLock::new(0)),
-            icon_cache_dir,
-            cache_path,
+            entries: Arc::new(RwLock::new(Arc::new(entries))),
+            is_indexing: Arc::new(AtomicBool::new(false)),
+            stats: Arc::new(RwLock::new(IndexStats::default())),
+            usage: Arc::new(RwLock::new(load(persistent, &usage_path, "usage history"))),
+            selections: Arc::new(RwLock::new(load(persistent, &selections_path, "remembered selections"))),
+            history: Arc::new(RwLock::new(load(persistent, &history_path, "query history"))),
+            state_writes: Arc::new(Mutex::new(())),
+            icon_updates: broadcast::channel(ICON_UPDATE_CAPACITY).0,
+            icon_extraction_disabled: Arc::new(AtomicBool::new(false)),
+            persistent,
+            icon_cache_dir: file("icons"),
+            icon_access_path: file("icon_access.json"),
+            cache_path: file("index_cache.json"),
+            usage_path,
+            selections_path,
+            history_path,
+        }
+    }
+
+    /// Receiver of icons extracted from now on, for upgrading placeholders
+    pub fn icon_updates(&self) -> broadcast::Receiver<IconReady> {
+        self.icon_updates.subscribe()
+    }
+
+    /// Shared snapshot of the current entries
+    pub async fn get_entries(&self) -> Arc<Vec<ProgramEntry>> {
+        Arc::clone(&*self.entries.read().await)
+    }
+
+    /// Write the current entries to `path` as pretty JSON, in the same shape
+    /// as the cache (with resolved targets and sources). Returns how many were written.
+    pub async fn export_json(&self, path: &Path) -> io::Result<usize> {
+        let entries = self.get_entries().await;
+        let json = serde_json::to_string_pretty(&*entries).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, json)?;
+        Ok(entries.len())
+    }
+
+    /// Folder holding extracted program icons
+    pub fn icon_cache_dir(&self) -> &Path {
+        &self.icon_cache_dir
+    }
+
+    /// Delete every cached icon so the next index re-extracts them.
+    /// Files that can't be removed (e.g. locked) are skipped. Returns the number removed.
+    pub fn clear_icon_cache(&self) -> usize {
+        // Give extraction another chance along with the emptied cache
+        self.icon_extraction_disabled.store(false, Ordering::Relaxed);
+        if !self.persistent {
+            return 0;
         }
+        let Ok(dir) = fs::read_dir(&self.icon_cache_dir) else {
+            return 0;
+        };
+
+        let mut removed = 0;
+        for entry in dir.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            match fs::remove_file(&path) {
+                Ok(()) => removed += 1,
+                Err(e) => log::warn!("Could not remove cached icon {}: {}", path.display(), e),
+            }
+        }
+        log::info!("Cleared {} cached icons", removed);
+        removed
+    }
+
+    /// Drop the entry at `path` and its cached icon, and rewrite the cache.
+    /// It comes back on the next reindex. Returns whether an entry was removed.
+    pub async fn remove_entry(&self, path: &Path) -> bool {
+        let _writing = self.state_writes.lock().await;
+        let (removed, snapshot) = {
+            let mut entries = self.entries.write().await;
+            let Some(position) = entries.iter().position(|e| e.path == path) else {
+                return false;
+            };
+            let mut remaining = Vec::clone(&entries);
+            let removed = remaining.remove(position);
+            *entries = Arc::new(remaining);
+            (removed, Arc::clone(&entries))
+        };
+        log::info!("Removed {} from the index", path.display());
+        if !self.persistent {
+            return true;
+        }
+
+        let cached_icon = removed.icon_path.filter(|icon| icon.starts_with(&self.icon_cache_dir));
+        let cache_path = self.cache_path.clone();
+        let saved = tokio::task::spawn_blocking(move || {
+            if let Some(icon) = cached_icon {
+                if let Err(e) = fs::remove_file(&icon) {
+                    log::warn!("Could not remove cached icon {}: {}", icon.display(), e);
+                }
+            }
+            Self::save_cache_sync(&cache_path, &snapshot);
+        });
+        if let Err(e) = saved.await {
+            log::warn!("Failed to write index cache: {}", e);
+        }
+        true
+    }
+
+    /// Statistics from the most recent indexing run
+    pub async fn stats(&self) -> IndexStats {
+        self.stats.read().await.clone()
+    }
+
+    /// Folders the most recent indexing run could not read
+    pub async fn errors(&self) -> Vec<IndexError> {
+        self.stats.read().await.errors.clone()
+    }
+
+    /// Snapshot of the launch history, for ranking
+    pub async fn usage(&self) -> UsageMap {
+        self.usage.read().await.clone()
+    }
+
+    /// Launch history of a single program
+    pub async fn usage_for(&self, path: &Path) -> Option<UsageStat> {
+        self.usage.read().await.get(path).cloned()
+    }
+
+    /// Count a launch of `path` and persist the history
+    pub async fn record_launch(&self, path: &Path) {
+        let now = unix_now();
+
+        let _writing = self.state_writes.lock().await;
+        let usage = {
+            let mut usage = self.usage.write().await;
+            let stat = usage.entry(path.to_path_buf()).or_default();
+            stat.launches = stat.launches.saturating_add(1);
+            stat.last_launched = now;
+            usage.clone()
+        };
+
+        self.save_json(&self.usage_path, usage, "usage history").await;
     }
 
-    pub async fn is_indexing(&self) -> bool {
-        *self.is_indexing.read().await
+    /// Result last chosen for a normalized query
+    pub async fn remembered_selection(&self, query: &str) -> Option<PathBuf> {
+        let selections = self.selections.read().await;
+        selections.iter().rev().find(|(q, _)| q == query).map(|(_, path)| path.clone())
     }
 
-    pub async fn indexed_count(&self) -> usize {
-        *self.indexed_count.read().await
+    /// Remember `path` as the choice for a normalized query, evicting the
+    /// least recently used queries beyond `MAX_REMEMBERED_SELECTIONS`
+    pub async fn remember_selection(&self, query: &str, path: &Path) {
+        let _writing = self.state_writes.lock().await;
+        let selections = {
+            let mut selections = self.selections.write().await;
+            selections.retain(|(q, _)| q != query);
+            selections.push((query.to_string(), path.to_path_buf()));
+            let excess = selections.len().saturating_sub(MAX_REMEMBERED_SELECTIONS);
+            selections.drain(..excess);
+            selections.clone()
+        };
+
+        self.save_json(&self.selections_path, selections, "remembered selections").await;
+    }
+
+    /// Queries that led to a launch, most recent last
+    pub async fn query_history(&self) -> Vec<String> {
+        self.history.read().await.clone()
+    }
+
+    /// Add a launched query to the history, moving a repeat to the end and
+    /// dropping the oldest beyond `MAX_QUERY_HISTORY`
+    pub async fn remember_query(&self, query: &str) {
+        let _writing = self.state_writes.lock().await;
+        let history = {
+            let mut history = self.history.write().await;
+            history.retain(|q| q != query);
+            history.push(query.to_string());
+            let excess = history.len().saturating_sub(MAX_QUERY_HISTORY);
+            history.drain(..excess);
+            history.clone()
+        };
+
+        self.save_json(&self.history_path, history, "query history").await;
+    }
+
+    /// Write a snapshot of a state file on a blocking thread, so readers
+    /// aren't held up by the disk, unless running without a cache folder
+    async fn save_json<T: Serialize + Send + 'static>(&self, path: &Path, value: T, what: &'static str) {
+        if !self.persistent {
+            return;
+        }
+        let path = path.to_path_buf();
+        if let Err(e) = tokio::task::spawn_blocking(move || write_json(&path, &value, what)).await {
+            log::warn!("Failed to write {}: {}", what, e);
+        }
+    }
+
+    /// Load cached index from disk. Returns how many entries were loaded,
+    /// or `None` without a readable cache.
+    pub async fn load_cache(&self) -> Option<usize> {
+        if !self.persistent {
+            return None;
+        }
+        // Reading and parsing a large cache takes a while; keep it off the async workers
+        let cache_path = self.cache_path.clone();
+        let cached = tokio::task::spawn_blocking(move || Self::read_cache_sync(&cache_path))
+            .await
+            .ok()
+            .flatten();
+
+        let cached = cached?;
+        let count = cached.len();
+        *self.entries.write().await = Arc::new(cached);
+        Some(count)
     }
 
-    pub async fn get_entries(&self) -> Vec<ProgramEntry> {
-        self.entries.read().await.clone()
+    /// Time since the disk cache was last written, if there is one
+    pub fn cache_age(&self) -> Option<Duration> {
+        if !self.persistent {
+            return None;
+        }
+        let modified = fs::metadata(&self.cache_path).and_then(|m| m.modified()).ok()?;
+        // A timestamp in the future counts as fresh
+        Some(modified.elapsed().unwrap_or_default())
     }
 
-    /// Load cached index from disk. Returns true if cache was loaded.
-    pub async fn load_cache(&self) -> bool {
-        if !self.cache_path.exists() {
-            return false;
+    /// Read and parse the disk cache, if present and valid.
+    fn read_cache_sync(cache_path: &Path) -> Option<Vec<ProgramEntry>> {
+        if !cache_path.exists() {
+            return None;
         }
-        match fs::read_to_string(&self.cache_path) {
+        match fs::read_to_string(cache_path) {
             Ok(data) => match serde_json::from_str::<Vec<ProgramEntry>>(&data) {
                 Ok(cached) => {
-                    let count = cached.len();
-                    {
-                        let mut e = self.entries.write().await;
-                        *e = cached;
-                    }
-                    {
-                        let mut c = self.indexed_count.write().await;
-                        *c = count;
-                    }
-                    true
+                    log::info!("Loaded {} cached entries from {}", cached.len(), cache_path.display());
+                    Some(cached)
+                }
+                Err(e) => {
+                    log::warn!("Ignoring unreadable index cache: {}", e);
+                    None
                 }
-                Err(_) => false,
             },
-            Err(_) => false,
+            Err(e) => {
+                log::warn!("Failed to read index cache {}: {}", cache_path.display(), e);
+                None
+            }
         }
     }
 
     /// Save current index to disk cache.
-    fn save_cache_sync(cache_path: &PathBuf, entries: &[ProgramEntry]) {
+    fn save_cache_sync(cache_path: &Path, entries: &[ProgramEntry]) {
         if let Ok(json) = serde_json::to_string(entries) {
-            let _ = fs::write(cache_path, json);
+            if let Err(e) = fs::write(cache_path, json) {
+                log::warn!("Failed to write index cache {}: {}", cache_path.display(), e);
+            }
         }
     }
 
-    pub async fn start_indexing(&self) {
+    /// Start indexing in the background. Returns a progress receiver that
+    /// closes once the run has finished and the cache is written, or `None`
+    /// if a run is already in progress.
+    pub async fn start_indexing(&self, options: IndexOptions) -> Option<watch::Receiver<IndexProgress>> {
+        if self
+            .is_indexing
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
         {
-            let mut indexing = self.is_indexing.write().await;
-            if *indexing {
-                return;
-            }
-            *indexing = true;
+            return None;
         }
+        let claim = IndexingClaim(Arc::clone(&self.is_indexing));
+        // Every run retries, e.g. after the shell has finished starting up
+        self.icon_extraction_disabled.store(false, Ordering::Relaxed);
+        let icon_extraction_disabled = Arc::clone(&self.icon_extraction_disabled);
+
+        let (progress_tx, progress_rx) = watch::channel(IndexProgress::default());
 
         let entries = Arc::clone(&self.entries);
-        let is_indexing = Arc::clone(&self.is_indexing);
-        let indexed_count = Arc::clone(&self.indexed_count);
+        let shared_stats = Arc::clone(&self.stats);
         let icon_cache_dir = self.icon_cache_dir.clone();
+        let icon_access_path = self.icon_access_path.clone();
         let cache_path = self.cache_path.clone();
+        let icon_updates = self.icon_updates.clone();
+        let state_writes = Arc::clone(&self.state_writes);
+        let persistent = self.persistent;
 
         tokio::task::spawn_blocking(move || {
-            let mut programs: Vec<ProgramEntry> = Vec::new();
-            let mut seen: HashMap<String, bool> = HashMap::new();
-
-            // Index Start Menu (highest priority)
-            let start_menu_paths = get_start_menu_paths();
-            for start_path in start_menu_paths {
-                if start_path.exists() {
-                    index_directory(&start_path, ProgramSource::StartMenu, &mut programs, &mut seen, &icon_cache_dir);
+            log::info!("Indexing started");
+            let started = Instant::now();
+            // Partial results beat an empty window, but would replace a full index
+            let stream = entries.blocking_read().is_empty();
+            let mut run = IndexRun {
+                programs: Vec::new(),
+                stats: IndexStats::default(),
+                progress: &progress_tx,
+                last_progress: Instant::now(),
+                stream_to: stream.then_some(&*entries),
+                published: 0,
+            };
+
+            // Index the Start Menu (highest priority), Desktop and Downloads, then Program Files
+            for (dir, source, depth) in built_in_dirs(&options) {
+                if dir.exists() {
+                    index_directory(&dir, source, depth, &options, &mut run);
                 }
             }
 
-            // Index Program Files
-            let program_dirs = [
-                PathBuf::from("C:\\Program Files"),
-                PathBuf::from("C:\\Program Files (x86)"),
-            ];
-            for dir in &program_dirs {
+            // Index user-configured extra directories
+            for (dir, depth) in &options.extra_paths {
                 if dir.exists() {
-                    index_directory(dir, ProgramSource::ProgramFiles, &mut programs, &mut seen, &icon_cache_dir);
+                    index_directory(dir, ProgramSource::ProgramFiles, *depth, &options, &mut run);
+                } else {
+                    // Unlike the built-in folders, these were asked for explicitly
+                    run.stats.errors.push(IndexError {
+                        path: dir.clone(),
+                        source: ProgramSource::ProgramFiles,
+                        message: "folder not found".to_string(),
+                    });
                 }
             }
 
+            let IndexRun { programs, mut stats, .. } = run;
+
+            // Before icons, so merged-away entries don't cost an extraction
+            let (mut programs, merged) = merge_same_targets(programs);
+            stats.merged = merged;
+            // Also before icons, which are cached per display name
+            disambiguate_names(&mut programs);
+
+            // Icons are served from files, so there are none without a cache folder.
+            // Cached ones are attached now; the rest follow once results are shown.
+            if persistent {
+                attach_cached_icons(&mut programs, &icon_cache_dir);
+            }
+
             // Sort by source priority and name
             programs.sort_by(|a, b| {
-                let priority_a = match a.source {
-                    ProgramSource::StartMenu => 0,
-                    ProgramSource::ProgramFiles => 1,
-                };
-                let priority_b = match b.source {
-                    ProgramSource::StartMenu => 0,
-                    ProgramSource::ProgramFiles => 1,
-                };
-                priority_a.cmp(&priority_b).then_with(|| a.display_name.cmp(&b.display_name))
+                a.source
+                    .priority()
+                    .cmp(&b.source.priority())
+                    .then_with(|| a.display_name.cmp(&b.display_name))
             });
 
             let count = programs.len();
+            for program in &programs {
+                *stats.per_source.entry(program.source.clone()).or_insert(0) += 1;
+            }
+            stats.last_duration = Some(started.elapsed());
+            log::info!(
+                "Indexing finished: {} entries from {} files ({} merged, {} links skipped) in {:.2?}",
+                count,
+                stats.files_walked,
+                stats.merged,
+                stats.reparse_points,
+                started.elapsed()
+            );
 
             // Update shared state in blocking context
             let rt = tokio::runtime::Handle::current();
             rt.block_on(async {
                 {
                     let mut e = entries.write().await;
-                    *e = programs;
+                    *e = Arc::new(programs.clone());
                 }
                 {
-                    let mut cnt = indexed_count.write().await;
-                    *cnt = count;
+                    let mut st = shared_stats.write().await;
+                    *st = stats;
                 }
-                {
-                    let mut idx = is_indexing.write().await;
-                    *idx = false;
-                }
-                // Save cache to disk
-                let entries_snapshot = entries.read().await.clone();
-                ProgramIndex::save_cache_sync(&cache_path, &entries_snapshot);
             });
+            progress_tx.send_replace(IndexProgress { found: count, done: true, partial: false });
+
+            if persistent {
+                // Each icon is announced as it lands, so visible rows can swap
+                // their placeholder without waiting for the rest
+                let missing: Vec<usize> = (0..programs.len()).filter(|&i| programs[i].icon_path.is_none()).collect();
+                let extraction = IconExtraction {
+                    cache_dir: &icon_cache_dir,
+                    concurrency: options.icon_concurrency,
+                    disabled: &icon_extraction_disabled,
+                    extract: &extract_icon,
+                };
+                let extracted = extract_icons(&programs, &missing, &extraction, &|program: &ProgramEntry, icon: &Path| {
+                    // Nobody listening (e.g. headless indexing) is fine
+                    let _ = icon_updates.send(IconReady { path: program.path.clone(), icon_path: icon.to_path_buf() });
+                });
+                let icons: HashMap<&Path, PathBuf> = extracted
+                    .into_iter()
+                    .map(|(i, icon)| (programs[i].path.as_path(), icon))
+                    .collect();
+
+                let writing = state_writes.blocking_lock();
+                let (snapshot, in_use) = rt.block_on(async {
+                    // Apply to the current entries, which may have changed (e.g. an entry removed)
+                    let mut e = entries.write().await;
+                    if !icons.is_empty() {
+                        let updated: Vec<ProgramEntry> = e
+                            .iter()
+                            .map(|entry| match (&entry.icon_path, icons.get(entry.path.as_path())) {
+                                (None, Some(icon)) => ProgramEntry { icon_path: Some(icon.clone()), ..entry.clone() },
+                                _ => entry.clone(),
+                            })
+                            .collect();
+                        *e = Arc::new(updated);
+                    }
+                    let in_use: HashSet<PathBuf> = e.iter().filter_map(|entry| entry.icon_path.clone()).collect();
+                    (Arc::clone(&e), in_use)
+                });
+                // Searches can go on while the cache is written
+                ProgramIndex::save_cache_sync(&cache_path, &snapshot);
+                drop(writing);
+
+                let mut last_used = track_icon_use(&icon_access_path, &in_use);
+                if let Some(limit) = options.icon_cache_limit {
+                    evict_icons(&icon_cache_dir, limit, &in_use, &last_used);
+                }
+                // Forget icons that were evicted or cleared
+                last_used.retain(|name, _| icon_cache_dir.join(name).exists());
+                write_json(&icon_access_path, &last_used, "icon access times");
+            }
+
+            drop(claim);
+            // Dropping the sender closes the channel once the index and cache are written
+            drop(progress_tx);
         });
+
+        Some(progress_rx)
+    }
+}
+
+/// Where the cache goes when none is configured, most preferred first
+fn default_cache_dirs() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    candidates.extend(dirs::cache_dir().map(|dir| dir.join("locksearch")));
+    if let Some(exe_dir) = std::env::current_exe().ok().as_deref().and_then(Path::parent) {
+        candidates.push(exe_dir.join("cache"));
+    }
+    candidates.push(std::env::temp_dir().join("locksearch"));
+    candidates
+}
+
+/// Current Unix time in seconds
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Read a small JSON state file, starting empty if it is missing or unreadable
+fn read_json<T: DeserializeOwned + Default>(path: &Path, what: &str) -> T {
+    let Ok(data) = fs::read_to_string(path) else {
+        return T::default();
+    };
+    serde_json::from_str(&data).unwrap_or_else(|e| {
+        log::warn!("Ignoring unreadable {} {}: {}", what, path.display(), e);
+        T::default()
+    })
+}
+
+/// Write a small JSON state file, logging failures
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T, what: &str) {
+    match serde_json::to_string(value) {
+        Ok(json) => {
+            if let Err(e) = fs::write(path, json) {
+                log::warn!("Failed to write {} {}: {}", what, path.display(), e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize {}: {}", what, e),
     }
 }
 
+/// Start Menu "Programs" folders: the shell's known folders, which follow
+/// other system drives and redirected profiles, each falling back to its
+/// usual location when it can't be resolved. Duplicates are removed.
 fn get_start_menu_paths() -> Vec<PathBuf> {
+    const PROGRAMS: &str = "Microsoft\\Windows\\Start Menu\\Programs";
+
+    let [common, user] = platform::start_menu_program_dirs();
+    let mut paths = vec![common.unwrap_or_else(|| env_dir("ProgramData", "C:\\ProgramData").join(PROGRAMS))];
+    // Roaming (%APPDATA%) profile folder
+    paths.extend(user.or_else(|| dirs::data_dir().map(|dir| dir.join(PROGRAMS))));
+    // Some per-user installs use the local profile, which has no known folder
+    paths.extend(dirs::data_local_dir().map(|dir| dir.join(PROGRAMS)));
+
+    let mut seen = HashSet::new();
+    paths.retain(|path| seen.insert(path.to_string_lossy().to_lowercase()));
+    paths
+}
+
+/// Program Files folders (native, then 32-bit) from the shell, each falling
+/// back to the environment and then to its usual place on `C:`
+fn get_program_files_paths() -> Vec<PathBuf> {
+    let [native, x86] = platform::program_files_dirs();
+    let mut paths = vec![
+        native.unwrap_or_else(|| env_dir("ProgramFiles", "C:\\Program Files")),
+        x86.unwrap_or_else(|| env_dir("ProgramFiles(x86)", "C:\\Program Files (x86)")),
+    ];
+    // A 32-bit system has only the one folder, which both names resolve to
+    paths.dedup_by(|a, b| a.to_string_lossy().eq_ignore_ascii_case(&b.to_string_lossy()));
+    paths
+}
+
+/// The folder in environment variable `var`, or `default` when it is unset
+fn env_dir(var: &str, default: &str) -> PathBuf {
+    std::env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(default))
+}
+
+/// Folder levels scanned on the Desktop and in Downloads
+const DESKTOP_DEPTH: usize = 2;
+
+/// The built-in folders a run walks, in priority order, with the source and
+/// depth of each. Disabled sources add none, so their folders aren't touched.
+fn built_in_dirs(options: &IndexOptions) -> Vec<(PathBuf, ProgramSource, usize)> {
+    let mut dirs = Vec::new();
+    if options.index_start_menu {
+        dirs.extend(
+            get_start_menu_paths()
+                .into_iter()
+                .map(|dir| (dir, ProgramSource::StartMenu, options.start_menu_depth)),
+        );
+    }
+    dirs.extend(get_desktop_paths(options).into_iter().map(|dir| (dir, ProgramSource::Desktop, DESKTOP_DEPTH)));
+    if options.index_program_files {
+        dirs.extend(
+            get_program_files_paths()
+                .into_iter()
+                .map(|dir| (dir, ProgramSource::ProgramFiles, options.program_files_depth)),
+        );
+    }
+    dirs
+}
+
+/// Desktop folders (current user, then public) and Downloads, as enabled
+fn get_desktop_paths(options: &IndexOptions) -> Vec<PathBuf> {
     let mut paths = Vec::new();
-    paths.push(PathBuf::from("C:\\ProgramData\\Microsoft\\Windows\\Start Menu\\Programs"));
-    if let Some(appdata) = dirs::data_dir() {
-        let user_start = appdata
-            .parent()
-            .map(|p| p.join("Roaming\\Microsoft\\Windows\\Start Menu\\Programs"));
-        if let Some(p) = user_start {
-            paths.push(p);
-        }
+    if options.index_desktop {
+        paths.extend(dirs::desktop_dir());
+        paths.extend(platform::public_desktop_dir());
+    }
+    if options.index_downloads {
+        paths.extend(dirs::download_dir());
     }
     paths
 }
 
+/// Held by a running index; releases `is_indexing` when dropped, even if
+/// the run panics, so a failed run doesn't block every later one
+struct IndexingClaim(Arc<AtomicBool>);
+
+impl Drop for IndexingClaim {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// State accumulated across all directories of one indexing run
+struct IndexRun<'a> {
+    programs: Vec<ProgramEntry>,
+    stats: IndexStats,
+    progress: &'a watch::Sender<IndexProgress>,
+    /// When `progress` was last sent, to keep updates `progress_interval` apart
+    last_progress: Instant,
+    /// Where entries found so far are published with each progress update,
+    /// unsorted and not yet merged; the finished run replaces them
+    stream_to: Option<&'a RwLock<Arc<Vec<ProgramEntry>>>>,
+    /// How many of `programs` are in `stream_to` already
+    published: usize,
+}
+
 fn index_directory(
-    dir: &PathBuf,
+    dir: &Path,
     source: ProgramSource,
-    programs: &mut Vec<ProgramEntry>,
-    seen: &mut HashMap<String, bool>,
-    icon_cache_dir: &PathBuf,
+    max_depth: usize,
+    options: &IndexOptions,
+    run: &mut IndexRun,
 ) {
-    let max_depth = match source {
-        ProgramSource::StartMenu => 5,
-        ProgramSource::ProgramFiles => 2,
-    };
-
     let extensions: &[&str] = match source {
         ProgramSource::StartMenu => &["lnk"],
+        ProgramSource::Desktop => &["lnk", "url"],
         ProgramSource::ProgramFiles => &["exe"],
+        ProgramSource::Filesystem | ProgramSource::Provider => return,
     };
 
-    for entry in WalkDir::new(dir)
+    // Junctions (e.g. localized Program Files folders) only lead to
+    // already-indexed content, or back up the tree
+    let skipped_links = Cell::new(0);
+    // Walk the extended-length form so folders nested past MAX_PATH are still read
+    let walker = WalkDir::new(platform::extended_length_path(dir))
         .max_depth(max_depth)
-        .follow_links(false)
+        .follow_links(options.follow_links)
         .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
+        .filter_entry(|entry| {
+            let is_link = !options.follow_links
+                && entry.depth() > 0
+                && entry.metadata().is_ok_and(|m| platform::is_reparse_point(&m));
+            if is_link {
+                log::debug!("Skipping reparse point {}", entry.path().display());
+                skipped_links.set(skipped_links.get() + 1);
+            }
+            !is_link
+        });
+
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) if e.depth() == 0 => {
+                // The folder itself is unreadable, so the whole source is missing
+                log::warn!("Cannot index {}: {}", dir.display(), e);
+                let message = e
+                    .io_error()
+                    .map_or_else(|| e.to_string(), |io| io.to_string());
+                run.stats.errors.push(IndexError {
+                    path: dir.to_path_buf(),
+                    source: source.clone(),
+                    message,
+                });
+                continue;
+            }
+            Err(e) => {
+                // Unreadable subfolders (e.g. WindowsApps) are expected
+                log::debug!("Skipping unreadable entry: {}", e);
+                continue;
+            }
+        };
+        // Keep the plain path unless it's too long to be used without the prefix
+        let path_buf = platform::usable_path(entry.path());
+        let path = path_buf.as_path();
 
         if !path.is_file() {
             continue;
         }
+        run.stats.files_walked += 1;
 
         let ext = path
             .extension()
             .and_then(|e| e.to_str())
             .map(|e| e.to_lowercase());
 
-        let is_valid_ext = ext.as_ref().map_or(false, |e| extensions.contains(&e.as_str()));
+        // An excluded extension wins over the source's own types
+        let is_valid_ext = ext.as_ref().is_some_and(|e| {
+            extensions.contains(&e.as_str()) && !options.excluded_extensions.contains(e)
+        });
 
         if !is_valid_ext {
             continue;
         }
 
-        // Skip uninstallers and updaters
-        let name_lower = path
+        let stem = path
             .file_stem()
-            .and_then(|n| n.to_str())
-            .map(|n| n.to_lowercase())
+            .map(|n| n.to_string_lossy().into_owned())
             .unwrap_or_default();
+        let name_lower = stem.to_lowercase();
 
-        if name_lower.contains("uninstall")
-            || name_lower.contains("uninst")
-            || name_lower.contains("update")
-            || name_lower.contains("updater")
-            || name_lower.contains("setup")
-        {
+        // Skip uninstallers and updaters. Shortcuts are curated, so keep them all.
+        if source == ProgramSource::ProgramFiles && is_blocklisted(&stem, &options.blocklist) {
+            log::debug!("Skipping installer/updater {}", path.display());
             continue;
         }
-
-        let (display_name, target_path) = get_display_name_and_target(path, &ext);
-        let key = display_name.to_lowercase();
-
-        // Avoid duplicates
-        if seen.contains_key(&key) {
+        if source == ProgramSource::ProgramFiles && is_helper(&name_lower, &options.helper_blocklist) {
+            log::debug!("Skipping helper executable {}", path.display());
             continue;
         }
-        seen.insert(key, true);
 
-        // Extract icon
-        let icon_path = extract_icon(&target_path, &display_name, icon_cache_dir);
+        // Duplicates are kept here and merged once every source is walked
+        let (display_name, target_path) = get_display_name_and_target(path, &ext);
+        let target = (target_path != path).then_some(target_path);
+        let opener = opener_for(target.as_deref().unwrap_or(path), &options.openers);
+        let target_name = target
+            .as_ref()
+            .and_then(|t| t.file_stem())
+            .map(|stem| stem.to_string_lossy().to_lowercase());
+        // Installers usually preserve file times, so creation marks when it appeared here
+        let modified = entry
+            .metadata()
+            .ok()
+            .and_then(|m| m.created().or_else(|_| m.modified()).ok())
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
 
-        programs.push(ProgramEntry {
+        run.programs.push(ProgramEntry {
             path: path.to_path_buf(),
             name: name_lower,
             display_name,
+            base_name: None,
             source: source.clone(),
-            icon_path,
+            // Filled in by `extract_icons` once the walk is done
+            icon_path: None,
+            target,
+            target_name,
+            modified,
+            opener,
         });
+
+        if run.last_progress.elapsed() >= options.progress_interval {
+            run.last_progress = Instant::now();
+            if let Some(shared) = run.stream_to {
+                // Only the new batch is copied, unless a search still holds the last list
+                let mut shared = shared.blocking_write();
+                Arc::make_mut(&mut shared).extend_from_slice(&run.programs[run.published..]);
+                run.published = run.programs.len();
+            }
+            run.progress.send_replace(IndexProgress {
+                found: run.programs.len(),
+                done: false,
+                partial: run.stream_to.is_some(),
+            });
+        }
     }
+
+    run.stats.reparse_points += skipped_links.get();
+}
+
+/// Architecture tags that trail installer names, e.g. "setup_x64"
+const ARCH_TAGS: &[&str] = &["x64", "x86", "amd64", "arm64", "win32", "win64"];
+
+/// Whether a file stem names an installer/updater: its last word, ignoring
+/// version numbers and architecture tags, is a pattern or a pattern with
+/// "r"/"er" added. "unins000", "Uninstaller", "app_uninstaller", "MySetup"
+/// and "setup_x64" match, while "Windows Update Assistant", "Setup Wizard
+/// Pro" or "Mindsetup" do not.
+fn is_blocklisted(stem: &str, blocklist: &[String]) -> bool {
+    let words = stem_words(stem);
+    let is_noise = |word: &String| {
+        let version = word.strip_prefix('v').unwrap_or(word);
+        version.chars().all(|c| c.is_ascii_digit()) || ARCH_TAGS.contains(&word.as_str())
+    };
+    let Some(last) = words.iter().rev().find(|word| !is_noise(word)) else {
+        return false;
+    };
+    let last = last.trim_end_matches(|c: char| c.is_ascii_digit());
+    blocklist.iter().any(|pattern| {
+        last.strip_prefix(pattern.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest == "r" || rest == "er")
+    })
+}
+
+/// Lowercased words of a file stem, split at separators and where a
+/// lowercase letter or digit meets an uppercase one: "app_uninstaller" gives
+/// "app" and "uninstaller", "MySetup" "my" and "setup", and "HTTPServer"
+/// "http" and "server". Digits stay with the word before them ("unins000").
+fn stem_words(stem: &str) -> Vec<String> {
+    let chars: Vec<char> = stem.chars().collect();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        let starts_word = c.is_uppercase()
+            && previous.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_ascii_digit()
+                    // The last capital of an acronym starts the next word
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if starts_word && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    words.extend((!word.is_empty()).then_some(word));
+    words
+}
+
+/// Whether a lowercased file stem, with separators dropped, contains one of
+/// `patterns`: "crashpad_handler" and "GoogleUpdateService" match "crashpad"
+/// and "service"
+fn is_helper(name_lower: &str, patterns: &[String]) -> bool {
+    let compact: String = name_lower.chars().filter(|c| c.is_alphanumeric()).collect();
+    patterns.iter().any(|pattern| compact.contains(pattern.as_str()))
+}
+
+/// The configured program for the extension of `file` (a shortcut's target)
+fn opener_for(file: &Path, openers: &HashMap<String, PathBuf>) -> Option<PathBuf> {
+    let ext = file.extension()?.to_string_lossy().to_lowercase();
+    openers.get(&ext).cloned()
+}
+
+/// Collapse entries that resolve to the same target, e.g. a public and a
+/// per-user Start Menu shortcut to one app, into the best-labeled of them.
+/// A human name ("Google Chrome") beats one that is just the executable's
+/// file stem ("chrome"); otherwise the earlier (higher priority) entry wins.
+/// Returns the kept entries in walk order and how many were merged away.
+fn merge_same_targets(programs: Vec<ProgramEntry>) -> (Vec<ProgramEntry>, usize) {
+    let mut kept: Vec<ProgramEntry> = Vec::with_capacity(programs.len());
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    let mut merged = 0;
+
+    for program in programs {
+        let target = program.target.as_deref().unwrap_or(&program.path);
+        let key = dedup_key(&program.path, target, &program.display_name);
+        match by_key.get(&key) {
+            Some(&index) => {
+                merged += 1;
+                let existing = &mut kept[index];
+                if has_human_label(&program) && !has_human_label(existing) {
+                    log::debug!("Merging {} into {}", existing.path.display(), program.path.display());
+                    *existing = program;
+                } else {
+                    log::debug!("Merging {} into {}", program.path.display(), existing.path.display());
+                }
+            }
+            None => {
+                by_key.insert(key, kept.len());
+                kept.push(program);
+            }
+        }
+    }
+
+    (kept, merged)
+}
+
+/// Folder levels above an entry searched for a name that tells it apart
+const DISAMBIGUATION_DEPTH: usize = 3;
+
+/// Give entries sharing a display name (e.g. two "Python" shortcuts of
+/// different versions) the nearest folder name that tells them apart:
+/// "Python (Python311)" and "Python (Python312)". Names stay as they are
+/// when no folder within `DISAMBIGUATION_DEPTH` levels differs for all.
+fn disambiguate_names(programs: &mut [ProgramEntry]) {
+    let mut groups: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, program) in programs.iter().enumerate() {
+        groups.entry(program.display_name.to_lowercase()).or_default().push(i);
+    }
+
+    for indices in groups.values().filter(|indices| indices.len() > 1) {
+        // Folder names above each entry's target, nearest first
+        let folders: Vec<Vec<String>> = indices
+            .iter()
+            .map(|&i| {
+                let location = programs[i].target.as_deref().unwrap_or(&programs[i].path);
+                location
+                    .ancestors()
+                    .skip(1)
+                    .take(DISAMBIGUATION_DEPTH)
+                    .filter_map(|folder| folder.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .collect()
+            })
+            .collect();
+
+        let level = (0..DISAMBIGUATION_DEPTH).find(|&level| {
+            let mut seen = HashSet::new();
+            folders
+                .iter()
+                .all(|names| names.get(level).is_some_and(|name| seen.insert(name.to_lowercase())))
+        });
+        let Some(level) = level else { continue };
+
+        for (&i, names) in indices.iter().zip(&folders) {
+            let program = &mut programs[i];
+            let display_name = format!("{} ({})", program.display_name, names[level]);
+            program.base_name = Some(std::mem::replace(&mut program.display_name, display_name));
+        }
+    }
+}
+
+/// Whether the display name is more than the executable's file stem,
+/// ignoring case and separators ("chrome" and "Chrome" are not)
+fn has_human_label(entry: &ProgramEntry) -> bool {
+    let compact = |s: &str| -> String {
+        s.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+    };
+    let stem = entry.target_name.as_deref().unwrap_or(&entry.name);
+    compact(&entry.display_name) != compact(stem)
+}
+
+/// Identity used to detect duplicates: the canonical resolved target when
+/// known, otherwise the display name and folder name (for shortcuts that
+/// couldn't be resolved). The folder keeps "Python 3.11\Python" and
+/// "Python 3.12\Python" apart, while the same shortcut in the public and
+/// per-user Start Menu still matches.
+fn dedup_key(path: &Path, target: &Path, display_name: &str) -> String {
+    let is_unresolved_link = target == path
+        && path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("lnk"));
+    if is_unresolved_link {
+        let folder = path
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        return format!("name:{}\\{}", folder, display_name.to_lowercase());
+    }
+
+    let canonical = fs::canonicalize(target).unwrap_or_else(|_| target.to_path_buf());
+    canonical.to_string_lossy().to_lowercase()
 }
 
 fn get_display_name_and_target(path: &std::path::Path, ext: &Option<String>) -> (String, PathBuf) {
-    if ext.as_ref().map_or(false, |e| e == "lnk") {
+    if ext.as_ref().is_some_and(|e| e == "lnk") {
         // Wrap in catch_unwind because the lnk crate can panic on malformed .lnk files
         // (e.g. unwrap() on None in header.rs for missing fields)
         let path_buf = path.to_path_buf();
@@ -292,14 +1279,13 @@ fn get_display_name_and_target(path: &std::path::Path, ext: &Option<String>) ->
             let link_info_opt = lnk.link_info().clone();
             if let Some(li) = link_info_opt {
                 if let Some(bp) = li.local_base_path() {
-                    target = PathBuf::from(bp);
+                    target = platform::usable_path(Path::new(&bp));
                 }
             }
             
             let display = name.unwrap_or_else(|| {
                 path.file_stem()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n.to_string())
+                    .map(|n| n.to_string_lossy().into_owned())
                     .unwrap_or_else(|| "Unknown".to_string())
             });
             return (display, target);
@@ -307,21 +1293,224 @@ fn get_display_name_and_target(path: &std::path::Path, ext: &Option<String>) ->
     }
     
     let name = path.file_stem()
-        .and_then(|n| n.to_str())
-        .map(|n| n.to_string())
+        .map(|n| n.to_string_lossy().into_owned())
         .unwrap_or_else(|| "Unknown".to_string());
     (name, path.to_path_buf())
 }
 
-fn extract_icon(exe_path: &PathBuf, display_name: &str, cache_dir: &PathBuf) -> Option<PathBuf> {
+/// Set `icon_path` of entries whose icon is already in the cache folder
+fn attach_cached_icons(programs: &mut [ProgramEntry], cache_dir: &Path) {
+    for program in programs {
+        let icon_path = icon_cache_path(&program.display_name, cache_dir);
+        if icon_path.exists() {
+            program.icon_path = Some(icon_path);
+        }
+    }
+}
+
+/// Failed extractions, with none succeeding, after which the shell is taken
+/// to be unable to provide icons at all (e.g. in a sandbox or on Server Core)
+const ICON_FAILURE_LIMIT: usize = 20;
+
+/// How one indexing run extracts icons
+struct IconExtraction<'a> {
+    cache_dir: &'a Path,
+    /// Threads extracting at once
+    concurrency: usize,
+    /// Set once the first `ICON_FAILURE_LIMIT` attempts of a run without any
+    /// cached icon all fail; entries then keep their placeholders
+    disabled: &'a AtomicBool,
+    /// Writes the icon of a file into the cache folder, see `extract_icon`
+    extract: &'a (dyn Fn(&Path, &str, &Path) -> Option<PathBuf> + Sync),
+}
+
+/// Extract icons for the entries at `indices` on at most `concurrency`
+/// threads at once, calling `on_extracted` as each one is written. Each
+/// request goes through the shell, and a first run asking for hundreds in
+/// parallel makes Explorer (and the whole desktop) stutter. Entries with
+/// the same target are extracted once and share the icon file.
+/// Returns the index of each entry that got an icon, with the icon's path.
+fn extract_icons(
+    programs: &[ProgramEntry],
+    indices: &[usize],
+    extraction: &IconExtraction,
+    on_extracted: &(dyn Fn(&ProgramEntry, &Path) + Sync),
+) -> Vec<(usize, PathBuf)> {
+    let disabled = extraction.disabled;
+    if disabled.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+    // With any icon cached, the shell has worked before; failures are then
+    // down to the programs themselves (e.g. uninstalled since)
+    let cold = programs.iter().all(|p| p.icon_path.is_none());
+    let groups = group_by_icon_source(programs, indices);
+    let next = AtomicUsize::new(0);
+    let failures = AtomicUsize::new(0);
+    let any_extracted = AtomicBool::new(false);
+    let workers = extraction.concurrency.clamp(1, groups.len().max(1));
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut extracted = Vec::new();
+                    // Take the next target until none are left
+                    loop {
+                        if disabled.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let Some(group) = groups.get(next.fetch_add(1, Ordering::Relaxed)) else { break };
+                        let program = &programs[group[0]];
+                        let source = program.target.as_deref().unwrap_or(&program.path);
+                        match (extraction.extract)(source, &program.display_name, extraction.cache_dir) {
+                            Some(icon) => {
+                                any_extracted.store(true, Ordering::Relaxed);
+                                // Every entry of the target shares the one extracted file
+                                for &i in group {
+                                    on_extracted(&programs[i], &icon);
+                                    extracted.push((i, icon.clone()));
+                                }
+                            }
+                            // Only the worker reaching the limit disables and logs
+                            None if cold
+                                && !any_extracted.load(Ordering::Relaxed)
+                                && failures.fetch_add(1, Ordering::Relaxed) + 1 == ICON_FAILURE_LIMIT =>
+                            {
+                                disabled.store(true, Ordering::Relaxed);
+                                log::warn!(
+                                    "Icon extraction disabled until the next index: the first {} attempts all failed",
+                                    ICON_FAILURE_LIMIT
+                                );
+                            }
+                            None => {}
+                        }
+                    }
+                    extracted
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle.join().unwrap_or_else(|panic| {
+                    // Icons it already wrote stay cached and are attached by the next run
+                    log::error!("An icon extraction worker panicked: {}", panic_message(&*panic));
+                    Vec::new()
+                })
+            })
+            .collect()
+    })
+}
+
+/// Text of a panic payload, when it is a string
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("(no message)")
+}
+
+/// `indices` grouped by the file their icon comes from (the shortcut target,
+/// else the entry itself), so several shortcuts to one program extract once.
+/// Groups are in order of their first entry.
+fn group_by_icon_source(programs: &[ProgramEntry], indices: &[usize]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut by_source: HashMap<String, usize> = HashMap::new();
+    for &i in indices {
+        let program = &programs[i];
+        let source = program.target.as_deref().unwrap_or(&program.path);
+        // Windows paths differ in case only when typed differently
+        let key = source.to_string_lossy().to_lowercase();
+        match by_source.get(&key) {
+            Some(&group) => groups[group].push(i),
+            None => {
+                by_source.insert(key, groups.len());
+                groups.push(vec![i]);
+            }
+        }
+    }
+    groups
+}
+
+/// When each cached icon was last used by an index, by file name, from the
+/// sidecar file at `access_path`, with the icons in `in_use` stamped now.
+/// File times can't tell: the access time isn't updated on most systems,
+/// and reading an icon to show it is not using it.
+fn track_icon_use(access_path: &Path, in_use: &HashSet<PathBuf>) -> HashMap<String, u64> {
+    let mut last_used: HashMap<String, u64> = read_json(access_path, "icon access times");
+    let now = unix_now();
+    for icon in in_use {
+        if let Some(name) = icon.file_name() {
+            last_used.insert(name.to_string_lossy().into_owned(), now);
+        }
+    }
+    last_used
+}
+
+/// Delete icons from `cache_dir` no entry uses (left behind by uninstalled
+/// programs), least recently used first per `last_used`, until the folder
+/// holds at most `limit` bytes. Icons in `in_use` are never deleted, even
+/// when they alone exceed the limit.
+fn evict_icons(cache_dir: &Path, limit: u64, in_use: &HashSet<PathBuf>, last_used: &HashMap<String, u64>) {
+    let Ok(dir) = fs::read_dir(cache_dir) else {
+        return;
+    };
+    let mut total: u64 = 0;
+    let mut unused: Vec<(u64, u64, PathBuf)> = Vec::new();
+    for entry in dir.filter_map(|entry| entry.ok()) {
+        let Some(metadata) = entry.metadata().ok().filter(|m| m.is_file()) else {
+            continue;
+        };
+        total += metadata.len();
+        let path = entry.path();
+        if !in_use.contains(&path) {
+            // Icons from before access times were tracked go first
+            let used = last_used.get(&*entry.file_name().to_string_lossy()).copied().unwrap_or(0);
+            unused.push((used, metadata.len(), path));
+        }
+    }
+    if total <= limit {
+        return;
+    }
+    unused.sort();
+
+    let mut removed = 0;
+    for (_, size, path) in unused {
+        if total <= limit {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                total -= size;
+                removed += 1;
+            }
+            Err(e) => log::warn!("Could not remove cached icon {}: {}", path.display(), e),
+        }
+    }
+    log::info!("Evicted {} unused icons to trim the icon cache to {} bytes", removed, limit);
+    if total > limit {
+        log::warn!(
+            "The icon cache limit of {} bytes is too small for the icons in use ({} bytes); raise max_icon_cache_mb",
+            limit,
+            total
+        );
+    }
+}
+
+/// Where the icon of an entry named `display_name` is cached
+fn icon_cache_path(display_name: &str, cache_dir: &Path) -> PathBuf {
     // Create a safe filename from display name
     let safe_name: String = display_name
         .chars()
         .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_')
         .take(50)
         .collect();
-    let icon_filename = format!("{}.png", safe_name.replace(' ', "_"));
-    let icon_path = cache_dir.join(&icon_filename);
+    cache_dir.join(format!("{}.png", safe_name.replace(' ', "_")))
+}
+
+fn extract_icon(exe_path: &Path, display_name: &str, cache_dir: &Path) -> Option<PathBuf> {
+    let icon_path = icon_cache_path(display_name, cache_dir);
 
     // Check if already cached
     if icon_path.exists() {
@@ -330,10 +1519,12 @@ fn extract_icon(exe_path: &PathBuf, display_name: &str, cache_dir: &PathBuf) ->
 
     // Try to extract icon
     let path_str = exe_path.to_string_lossy();
-    if let Ok(icon_data) = systemicons::get_icon(&path_str, 48) {
-        if fs::write(&icon_path, &icon_data).is_ok() {
-            return Some(icon_path);
-        }
+    match systemicons::get_icon(&path_str, 48) {
+        Ok(icon_data) => match fs::write(&icon_path, &icon_data) {
+            Ok(()) => return Some(icon_path),
+            Err(e) => log::warn!("Failed to cache icon {}: {}", icon_path.display(), e),
+        },
+        Err(e) => log::warn!("Failed to extract icon from {}: {:?}", path_str, e),
     }
 
     None
@@ -344,9 +1535,601 @@ impl Clone for ProgramIndex {
         Self {
             entries: Arc::clone(&self.entries),
             is_indexing: Arc::clone(&self.is_indexing),
-            indexed_count: Arc::clone(&self.indexed_count),
+            stats: Arc::clone(&self.stats),
+            usage: Arc::clone(&self.usage),
+            selections: Arc::clone(&self.selections),
+            history: Arc::clone(&self.history),
+            state_writes: Arc::clone(&self.state_writes),
+            icon_updates: self.icon_updates.clone(),
+            icon_extraction_disabled: Arc::clone(&self.icon_extraction_disabled),
+            persistent: self.persistent,
             icon_cache_dir: self.icon_cache_dir.clone(),
+            icon_access_path: self.icon_access_path.clone(),
             cache_path: self.cache_path.clone(),
+            usage_path: self.usage_path.clone(),
+            selections_path: self.selections_path.clone(),
+            history_path: self.history_path.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(display_name: &str, path: &str) -> ProgramEntry {
+        ProgramEntry {
+            path: PathBuf::from(path),
+            name: display_name.to_lowercase(),
+            display_name: display_name.to_string(),
+            base_name: None,
+            source: ProgramSource::ProgramFiles,
+            icon_path: None,
+            target: None,
+            target_name: None,
+            modified: None,
+            opener: None,
+        }
+    }
+
+    fn programs(count: usize) -> Vec<ProgramEntry> {
+        (0..count)
+            .map(|i| entry(&format!("App {}", i), &format!("C:\\Apps\\app{}.exe", i)))
+            .collect()
+    }
+
+    /// Extract icons for every entry without one with `extract`, returning
+    /// how often it was called and whether extraction got disabled
+    fn run_extraction(
+        programs: &[ProgramEntry],
+        extract: &(dyn Fn(&Path, &str, &Path) -> Option<PathBuf> + Sync),
+    ) -> (usize, bool) {
+        let calls = AtomicUsize::new(0);
+        let disabled = AtomicBool::new(false);
+        let counting = |path: &Path, name: &str, dir: &Path| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            extract(path, name, dir)
+        };
+        let extraction = IconExtraction {
+            cache_dir: Path::new("icons"),
+            concurrency: 1,
+            disabled: &disabled,
+            extract: &counting,
+        };
+        let missing: Vec<usize> = (0..programs.len()).filter(|&i| programs[i].icon_path.is_none()).collect();
+        extract_icons(programs, &missing, &extraction, &|_, _| {});
+        (calls.load(Ordering::Relaxed), disabled.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn failing_extraction_stops_at_the_limit() {
+        let (calls, disabled) = run_extraction(&programs(50), &|_, _, _| None);
+        assert!(disabled);
+        assert_eq!(calls, ICON_FAILURE_LIMIT);
+    }
+
+    #[test]
+    fn failures_on_a_warm_cache_keep_extraction_enabled() {
+        let mut programs = programs(50);
+        programs[0].icon_path = Some(PathBuf::from("icons\\App_0.png"));
+        let (calls, disabled) = run_extraction(&programs, &|_, _, _| None);
+        assert!(!disabled);
+        assert_eq!(calls, 49);
+    }
+
+    #[test]
+    fn a_success_keeps_extraction_enabled() {
+        let (calls, disabled) = run_extraction(&programs(50), &|path, _, _| {
+            path.to_string_lossy().ends_with("app0.exe").then(|| PathBuf::from("icons\\App_0.png"))
+        });
+        assert!(!disabled);
+        assert_eq!(calls, 50);
+    }
+
+    #[test]
+    fn clearing_the_icon_cache_reenables_extraction() {
+        let index = ProgramIndex::in_memory();
+        index.icon_extraction_disabled.store(true, Ordering::Relaxed);
+        index.clear_icon_cache();
+        assert!(!index.icon_extraction_disabled.load(Ordering::Relaxed));
+    }
+
+    /// A fresh, empty folder for a test to write to
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("locksearch-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn streamed_entries_are_published_once_as_they_are_found() {
+        let dir = temp_dir("stream");
+        for name in ["alpha", "beta", "gamma"] {
+            fs::write(dir.join(format!("{}.exe", name)), b"").unwrap();
+        }
+        let options = IndexOptions { progress_interval: Duration::ZERO, ..IndexOptions::default() };
+        let (progress, _rx) = watch::channel(IndexProgress::default());
+        let shared = RwLock::new(Arc::new(Vec::new()));
+        let mut run = IndexRun {
+            programs: Vec::new(),
+            stats: IndexStats::default(),
+            progress: &progress,
+            last_progress: Instant::now(),
+            stream_to: Some(&shared),
+            published: 0,
+        };
+        index_directory(&dir, ProgramSource::ProgramFiles, 1, &options, &mut run);
+
+        // Each update published the entries found since the one before
+        let mut names: Vec<String> = shared.blocking_read().iter().map(|e| e.display_name.clone()).collect();
+        names.sort();
+        assert_eq!(names, ["alpha", "beta", "gamma"]);
+        assert!(progress.borrow().partial);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn desktop_shortcuts_are_indexed() {
+        let desktop = temp_dir("desktop").join("Desktop");
+        fs::create_dir_all(desktop.join("Tools")).unwrap();
+        fs::write(desktop.join("Editor.lnk"), b"").unwrap();
+        fs::write(desktop.join("Docs.url"), "[InternetShortcut]\r\nURL=https://example.com/\r\n").unwrap();
+        fs::write(desktop.join("Tools").join("Terminal.lnk"), b"").unwrap();
+        fs::write(desktop.join("notes.txt"), b"").unwrap();
+        let (progress, _rx) = watch::channel(IndexProgress::default());
+        let mut run = IndexRun {
+            programs: Vec::new(),
+            stats: IndexStats::default(),
+            progress: &progress,
+            last_progress: Instant::now(),
+            stream_to: None,
+            published: 0,
+        };
+        index_directory(&desktop, ProgramSource::Desktop, DESKTOP_DEPTH, &IndexOptions::default(), &mut run);
+
+        let mut names: Vec<&str> = run.programs.iter().map(|e| e.display_name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["Docs", "Editor", "Terminal"]);
+        assert!(run.programs.iter().all(|e| e.source == ProgramSource::Desktop));
+        let _ = fs::remove_dir_all(desktop.parent().unwrap());
+    }
+
+    #[test]
+    fn an_excluded_extension_wins_over_the_source_types() {
+        let dir = temp_dir("exclude-ext");
+        for name in ["editor.lnk", "site.url", "Other.URL", "tool.exe"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        // The Desktop includes .lnk and .url; the config drops .url again
+        let config = Config { exclude_extensions: vec![" .URL ".to_string()], ..Config::default() };
+        let options = IndexOptions::from_config(&config);
+        let (progress, _rx) = watch::channel(IndexProgress::default());
+        let mut run = IndexRun {
+            programs: Vec::new(),
+            stats: IndexStats::default(),
+            progress: &progress,
+            last_progress: Instant::now(),
+            stream_to: None,
+            published: 0,
+        };
+        index_directory(&dir, ProgramSource::Desktop, 1, &options, &mut run);
+
+        let paths: Vec<PathBuf> = run.programs.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(paths, [dir.join("editor.lnk")]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn eviction_removes_the_least_recently_used_unused_icons() {
+        let dir = temp_dir("evict-unused");
+        for name in ["old.png", "recent.png", "used.png"] {
+            fs::write(dir.join(name), [0u8; 100]).unwrap();
+        }
+        let in_use = HashSet::from([dir.join("used.png")]);
+        let last_used = HashMap::from([("old.png".to_string(), 1), ("recent.png".to_string(), 2)]);
+
+        evict_icons(&dir, 200, &in_use, &last_used);
+        assert!(!dir.join("old.png").exists());
+        assert!(dir.join("recent.png").exists());
+        assert!(dir.join("used.png").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn eviction_keeps_icons_in_use_past_the_limit() {
+        let dir = temp_dir("evict-in-use");
+        for name in ["a.png", "b.png", "gone.png"] {
+            fs::write(dir.join(name), [0u8; 100]).unwrap();
+        }
+        let in_use = HashSet::from([dir.join("a.png"), dir.join("b.png")]);
+
+        evict_icons(&dir, 50, &in_use, &HashMap::new());
+        assert!(dir.join("a.png").exists());
+        assert!(dir.join("b.png").exists());
+        assert!(!dir.join("gone.png").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn icon_use_is_stamped_in_the_sidecar() {
+        let dir = temp_dir("icon-access");
+        let access_path = dir.join("icon_access.json");
+        write_json(&access_path, &HashMap::from([("old.png", 1u64), ("used.png", 1)]), "icon access times");
+
+        let last_used = track_icon_use(&access_path, &HashSet::from([dir.join("icons").join("used.png")]));
+        assert_eq!(last_used["old.png"], 1);
+        assert!(last_used["used.png"] > 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn openers_follow_the_extension_of_the_target() {
+        let mut config = Config::default();
+        config.open_with.insert(".TXT ".to_string(), "C:/Tools/notepad++.exe".to_string());
+        config.open_with.insert("pdf".to_string(), " ".to_string());
+        let openers = IndexOptions::from_config(&config).openers;
+        assert_eq!(openers.len(), 1);
+
+        let notepad = Some(PathBuf::from("C:/Tools/notepad++.exe"));
+        assert_eq!(opener_for(Path::new("D:/Notes/todo.Txt"), &openers), notepad);
+        assert_eq!(opener_for(Path::new("D:/Notes/report.pdf"), &openers), None);
+        assert_eq!(opener_for(Path::new("D:/Notes"), &openers), None);
+    }
+
+    fn default_blocklist() -> Vec<String> {
+        IndexOptions::default().blocklist
+    }
+
+    #[test]
+    fn stems_split_into_words() {
+        assert_eq!(stem_words("app_uninstaller"), ["app", "uninstaller"]);
+        assert_eq!(stem_words("MySetup"), ["my", "setup"]);
+        assert_eq!(stem_words("HTTPServer"), ["http", "server"]);
+        assert_eq!(stem_words("unins000"), ["unins000"]);
+        assert_eq!(stem_words("Setup-1.2 x64"), ["setup", "1", "2", "x64"]);
+    }
+
+    #[test]
+    fn installers_and_updaters_are_blocklisted() {
+        let blocklist = default_blocklist();
+        for stem in [
+            "unins000",
+            "Uninstall",
+            "Uninstaller",
+            "app_uninstaller",
+            "setup_x64",
+            "Setup-2.1.0",
+            "MySetup",
+            "app-updater",
+            "GoogleUpdate",
+            "setup_v2",
+        ] {
+            assert!(is_blocklisted(stem, &blocklist), "{} should be blocklisted", stem);
+        }
+    }
+
+    #[test]
+    fn programs_merely_containing_a_pattern_are_kept() {
+        let blocklist = default_blocklist();
+        for stem in ["Windows Update Assistant", "Setup Wizard Pro", "Mindsetup", "UpdateChecker", "x64", "7z"] {
+            assert!(!is_blocklisted(stem, &blocklist), "{} should be kept", stem);
+        }
+    }
+
+    #[test]
+    fn namesakes_are_told_apart_by_folder() {
+        let mut programs = vec![
+            entry("Python", "C:/Python311/python.exe"),
+            entry("Python", "C:/Python312/python.exe"),
+            entry("Notepad", "C:/Windows/notepad.exe"),
+        ];
+        disambiguate_names(&mut programs);
+
+        assert_eq!(programs[0].display_name, "Python (Python311)");
+        assert_eq!(programs[1].display_name, "Python (Python312)");
+        assert_eq!(programs[0].base_name.as_deref(), Some("Python"));
+        assert_eq!(programs[0].config_names().collect::<Vec<_>>(), ["Python (Python311)", "Python"]);
+        assert_eq!(programs[2].display_name, "Notepad");
+        assert_eq!(programs[2].base_name, None);
+    }
+
+    #[test]
+    fn namesakes_in_the_same_folder_keep_their_names() {
+        let mut programs = vec![
+            entry("Tool", "C:/Apps/tool.exe"),
+            entry("Tool", "C:/Apps/tool.lnk"),
+        ];
+        disambiguate_names(&mut programs);
+        assert!(programs.iter().all(|p| p.display_name == "Tool" && p.base_name.is_none()));
+    }
+
+    #[tokio::test]
+    async fn in_memory_index_counts_launches() {
+        let index = ProgramIndex::with_entries(programs(2));
+        let path = Path::new("C:\\Apps\\app0.exe");
+        index.record_launch(path).await;
+        index.record_launch(path).await;
+
+        let stat = index.usage_for(path).await.unwrap();
+        assert_eq!(stat.launches, 2);
+        assert!(stat.last_launched > 0);
+        assert!(index.usage_for(Path::new("C:\\Apps\\app1.exe")).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn removed_entries_leave_the_index() {
+        let index = ProgramIndex::with_entries(programs(3));
+        let path = Path::new("C:\\Apps\\app1.exe");
+        assert!(index.remove_entry(path).await);
+        assert!(!index.remove_entry(path).await);
+
+        let names: Vec<String> = index.get_entries().await.iter().map(|e| e.display_name.clone()).collect();
+        assert_eq!(names, ["App 0", "App 2"]);
+    }
+
+    #[tokio::test]
+    async fn the_latest_selection_per_query_is_remembered() {
+        let index = ProgramIndex::in_memory();
+        index.remember_selection("py", Path::new("python.exe")).await;
+        index.remember_selection("py", Path::new("pycharm.exe")).await;
+        assert_eq!(index.remembered_selection("py").await, Some(PathBuf::from("pycharm.exe")));
+        assert_eq!(index.remembered_selection("no").await, None);
+
+        // The least recently chosen queries are forgotten first
+        for i in 0..MAX_REMEMBERED_SELECTIONS {
+            index.remember_selection(&format!("q{}", i), Path::new("x.exe")).await;
+        }
+        assert_eq!(index.remembered_selection("py").await, None);
+        assert!(index.remembered_selection("q0").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn repeated_queries_move_to_the_end_of_the_history() {
+        let index = ProgramIndex::in_memory();
+        for query in ["code", "paint", "code"] {
+            index.remember_query(query).await;
+        }
+        assert_eq!(index.query_history().await, ["paint", "code"]);
+    }
+
+    #[tokio::test]
+    async fn launches_are_saved_with_a_cache_folder() {
+        let dir = temp_dir("usage");
+        let path = Path::new("C:\\Apps\\app0.exe");
+        ProgramIndex::with_storage(Some(&dir), Vec::new()).record_launch(path).await;
+
+        let reloaded = ProgramIndex::with_storage(Some(&dir), Vec::new());
+        assert_eq!(reloaded.usage_for(path).await.map(|stat| stat.launches), Some(1));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn only_one_index_runs_at_a_time() {
+        let index = ProgramIndex::in_memory();
+        let options = IndexOptions {
+            index_start_menu: false,
+            index_program_files: false,
+            index_desktop: false,
+            index_downloads: false,
+            extra_paths: Vec::new(),
+            ..IndexOptions::default()
+        };
+
+        // The run waits for the entries at its start, so it can't finish early
+        let entries = index.entries.write().await;
+        let mut progress = index.start_indexing(options.clone()).await.expect("the first run starts");
+        assert!(index.start_indexing(options.clone()).await.is_none());
+        drop(entries);
+
+        while progress.changed().await.is_ok() {}
+        let mut progress = index.start_indexing(options).await.expect("a run starts once the last one is done");
+        while progress.changed().await.is_ok() {}
+    }
+
+    #[test]
+    fn shortcuts_to_one_target_share_an_icon_group() {
+        let mut programs = programs(3);
+        programs[0].target = Some(PathBuf::from("C:\\Apps\\Editor.exe"));
+        programs[2].target = Some(PathBuf::from("c:\\apps\\editor.exe"));
+        assert_eq!(group_by_icon_source(&programs, &[0, 1, 2]), [vec![0, 2], vec![1]]);
+        // Only the given entries are grouped
+        assert_eq!(group_by_icon_source(&programs, &[2, 1]), [vec![2], vec![1]]);
+    }
+
+    #[test]
+    fn helpers_are_matched_by_name_part() {
+        let patterns = IndexOptions::default().helper_blocklist;
+        for name in ["crashpad_handler", "googleupdateservice", "notification_helper", "steamservice"] {
+            assert!(is_helper(name, &patterns), "{} should be a helper", name);
+        }
+        for name in ["chrome", "code", "obs64"] {
+            assert!(!is_helper(name, &patterns), "{} should be kept", name);
+        }
+    }
+
+    fn shortcut(display_name: &str, path: &Path, target: Option<&Path>) -> ProgramEntry {
+        ProgramEntry {
+            source: ProgramSource::StartMenu,
+            target: target.map(Path::to_path_buf),
+            target_name: target.map(|t| t.file_stem().unwrap().to_string_lossy().to_lowercase()),
+            ..entry(display_name, &path.to_string_lossy())
+        }
+    }
+
+    #[test]
+    fn shortcuts_to_one_target_merge_however_it_is_spelled() {
+        let dir = temp_dir("dedup-same");
+        fs::create_dir_all(dir.join("App")).unwrap();
+        let target = dir.join("App").join("app.exe");
+        fs::write(&target, b"").unwrap();
+        let detour = dir.join("App").join("..").join("App").join("app.exe");
+
+        let (kept, merged) = merge_same_targets(vec![
+            shortcut("App", &dir.join("Public").join("App.lnk"), Some(&target)),
+            shortcut("App", &dir.join("User").join("App.lnk"), Some(&detour)),
+            // Unresolved, but the same shortcut in both Start Menus
+            shortcut("Python", &dir.join("Public").join("Python 3.12").join("Python.lnk"), None),
+            shortcut("Python", &dir.join("User").join("Python 3.12").join("Python.lnk"), None),
+        ]);
+        assert_eq!(merged, 2);
+        assert_eq!(kept.len(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn shortcuts_to_one_target_keep_the_human_label() {
+        let dir = temp_dir("dedup-label");
+        let target = dir.join("chrome.exe");
+        fs::write(&target, b"").unwrap();
+        let stem_only = shortcut("chrome", &dir.join("User").join("chrome.lnk"), Some(&target));
+        let human = shortcut("Google Chrome", &dir.join("Public").join("Google Chrome.lnk"), Some(&target));
+
+        // Whichever comes first, the human name is kept
+        for programs in [vec![stem_only.clone(), human.clone()], vec![human.clone(), stem_only.clone()]] {
+            let (kept, merged) = merge_same_targets(programs);
+            assert_eq!(merged, 1);
+            assert_eq!(kept.len(), 1);
+            assert_eq!(kept[0].display_name, "Google Chrome");
+            assert_eq!(kept[0].path, human.path);
+        }
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn namesakes_with_other_targets_stay_apart() {
+        let dir = temp_dir("dedup-apart");
+        for folder in ["Tool 1", "Tool 2"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+            fs::write(dir.join(folder).join("tool.exe"), b"").unwrap();
+        }
+
+        let (kept, merged) = merge_same_targets(vec![
+            shortcut("Tool", &dir.join("Tool A.lnk"), Some(&dir.join("Tool 1").join("tool.exe"))),
+            shortcut("Tool", &dir.join("Tool B.lnk"), Some(&dir.join("Tool 2").join("tool.exe"))),
+            // Unresolved shortcuts of the same name in different folders
+            shortcut("Python", &dir.join("Python 3.11").join("Python.lnk"), None),
+            shortcut("Python", &dir.join("Python 3.12").join("Python.lnk"), None),
+        ]);
+        assert_eq!(merged, 0);
+        assert_eq!(kept.len(), 4);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn the_cache_holds_as_many_entries_as_the_run_that_wrote_it() {
+        let dir = temp_dir("cache-count");
+        let apps = dir.join("apps");
+        fs::create_dir_all(apps.join("sub")).unwrap();
+        for name in ["alpha.exe", "beta.exe", "sub/gamma.exe", "notes.txt"] {
+            fs::write(apps.join(name), b"").unwrap();
+        }
+        let cache = dir.join("cache");
+        fs::create_dir_all(cache.join("icons")).unwrap();
+        let options = IndexOptions {
+            index_start_menu: false,
+            index_program_files: false,
+            index_desktop: false,
+            index_downloads: false,
+            extra_paths: vec![(apps, 2)],
+            ..IndexOptions::default()
+        };
+
+        let index = ProgramIndex::with_storage(Some(&cache), Vec::new());
+        let mut progress = index.start_indexing(options).await.unwrap();
+        while progress.changed().await.is_ok() {}
+        let indexed = index.get_entries().await.len();
+        assert_eq!(indexed, 3);
+
+        let reloaded = ProgramIndex::with_storage(Some(&cache), Vec::new());
+        assert_eq!(reloaded.load_cache().await, Some(indexed));
+        assert_eq!(reloaded.get_entries().await.len(), indexed);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn entries_are_identified_by_their_path_in_any_case() {
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |entry: &ProgramEntry| {
+            let mut hasher = DefaultHasher::new();
+            entry.hash(&mut hasher);
+            hasher.finish()
+        };
+        let original = entry("Code", "C:\\Apps\\Code.exe");
+        let renamed = entry("Visual Studio Code", "C:\\Apps\\Code.exe");
+        let recased = entry("Code", "c:\\apps\\CODE.EXE");
+        let other = entry("Code", "C:\\Apps\\Code2.exe");
+
+        for same in [&renamed, &recased] {
+            assert_eq!(&original, same);
+            assert_eq!(hash(&original), hash(same));
+        }
+        assert_ne!(original, other);
+        assert_eq!(HashSet::from([original, renamed, recased, other]).len(), 2);
+    }
+
+    #[test]
+    fn no_more_than_the_configured_extractions_run_at_once() {
+        let programs = programs(40);
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        let extract = |_: &Path, name: &str, _: &Path| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            most.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(2));
+            running.fetch_sub(1, Ordering::SeqCst);
+            Some(PathBuf::from(format!("icons\\{}.png", name)))
+        };
+        let disabled = AtomicBool::new(false);
+        let extraction = IconExtraction { cache_dir: Path::new("icons"), concurrency: 3, disabled: &disabled, extract: &extract };
+
+        let indices: Vec<usize> = (0..programs.len()).collect();
+        let extracted = extract_icons(&programs, &indices, &extraction, &|_, _| {});
+        assert_eq!(extracted.len(), programs.len());
+        assert!(most.load(Ordering::SeqCst) <= 3);
+    }
+
+    #[tokio::test]
+    async fn exported_json_reads_back_as_the_entries() {
+        let dir = temp_dir("export");
+        let apps = dir.join("apps");
+        fs::create_dir_all(&apps).unwrap();
+        for name in ["alpha.exe", "beta.exe"] {
+            fs::write(apps.join(name), b"").unwrap();
+        }
+        let options = IndexOptions {
+            index_start_menu: false,
+            index_program_files: false,
+            index_desktop: false,
+            index_downloads: false,
+            extra_paths: vec![(apps, 1)],
+            ..IndexOptions::default()
+        };
+        let index = ProgramIndex::with_storage(None, Vec::new());
+        let mut progress = index.start_indexing(options).await.unwrap();
+        while progress.changed().await.is_ok() {}
+
+        let export = dir.join("export.json");
+        assert_eq!(index.export_json(&export).await.unwrap(), 2);
+        let read: Vec<ProgramEntry> = serde_json::from_str(&fs::read_to_string(&export).unwrap()).unwrap();
+        let entries = index.get_entries().await;
+        assert_eq!(read, *entries);
+        // Equality is by path only, so compare every field through the JSON
+        assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(&*entries).unwrap());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn disabled_sources_contribute_no_folders() {
+        let config = Config { index_program_files: false, index_desktop: false, index_downloads: false, ..Config::default() };
+        let dirs = built_in_dirs(&IndexOptions::from_config(&config));
+        assert!(!dirs.is_empty());
+        assert!(dirs.iter().all(|(_, source, _)| *source == ProgramSource::StartMenu));
+
+        let config = Config { index_start_menu: false, ..config };
+        assert!(built_in_dirs(&IndexOptions::from_config(&config)).is_empty());
+
+        let dirs = built_in_dirs(&IndexOptions::from_config(&Config::default()));
+        assert!(dirs.iter().any(|(_, source, _)| *source == ProgramSource::ProgramFiles));
+    }
+}
@@ -0,0 +1,156 @@
+//! What activating a result does, decided per entry so links and folders
+//! open the right way instead of everything going through the same call.
+
+use crate::config::LaunchMethod;
+use crate::indexer::ProgramEntry;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+/// How a result is opened when activated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResultAction {
+    /// Run the program, or let the shell open the file
+    Launch,
+    /// Open the link stored in an internet shortcut (web pages, `ms-settings:` pages)
+    OpenUri,
+    /// Open a folder in Explorer
+    RevealFolder,
+    /// List a folder's contents in the launcher (path navigation)
+    Navigate,
+}
+
+impl ResultAction {
+    /// Pick the action from the entry's extension (of the shortcut target, if
+    /// resolved). Start Menu and Program Files entries only differ in that
+    /// Start Menu shortcuts can point at links and folders.
+    pub fn for_entry(entry: &ProgramEntry) -> Self {
+        match entry.extension().as_deref() {
+            Some("url" | "website") => ResultAction::OpenUri,
+            // A shortcut target without an extension is a folder
+            None if entry.target.is_some() => ResultAction::RevealFolder,
+            _ => ResultAction::Launch,
+        }
+    }
+}
+
+/// Activate a result at `path` (with its resolved shortcut `target`, if any).
+/// A file with an `opener` is opened with that program when it can be started.
+pub fn perform(
+    action: ResultAction,
+    path: &Path,
+    target: Option<&Path>,
+    opener: Option<&Path>,
+    method: LaunchMethod,
+) -> io::Result<()> {
+    match action {
+        ResultAction::Launch => {
+            let program = target.unwrap_or(path);
+            if let Some(opener) = opener {
+                match process::Command::new(opener).arg(program).spawn() {
+                    Ok(_) => return Ok(()),
+                    // E.g. the opener was uninstalled; the association still works
+                    Err(e) => log::warn!("Could not open {} with {}: {}", program.display(), opener.display(), e),
+                }
+            }
+            if method == LaunchMethod::Direct && is_executable(program) {
+                spawn_direct(program)
+            } else {
+                open::that(path)
+            }
+        }
+        ResultAction::OpenUri => {
+            let file = target.unwrap_or(path);
+            match read_url(file) {
+                Some(url) => open::that(url),
+                // Let the shell handle shortcuts we can't read
+                None => open::that(path),
+            }
+        }
+        ResultAction::RevealFolder => open::that(target.unwrap_or(path)),
+        // Navigation happens in the UI; outside of it, just open the folder
+        ResultAction::Navigate => open::that(path),
+    }
+}
+
+/// Whether `path` can be started as a process rather than opened by association
+fn is_executable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exe"))
+}
+
+/// Start `program` as a child process running in its own folder, so it finds
+/// resources it loads by relative path. Shortcut arguments aren't passed.
+fn spawn_direct(program: &Path) -> io::Result<()> {
+    let mut command = process::Command::new(program);
+    if let Some(dir) = program.parent() {
+        command.current_dir(dir);
+    }
+    command.spawn().map(|_| ())
+}
+
+/// The `URL=` value of an internet shortcut (`.url`) file
+fn read_url(file: &Path) -> Option<String> {
+    let content = fs::read_to_string(file).ok()?;
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("URL="))
+        .map(|url| url.trim().to_string())
+        .find(|url| !url.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indexer::ProgramSource;
+    use std::path::PathBuf;
+
+    fn entry(source: ProgramSource, path: &str, target: Option<&str>) -> ProgramEntry {
+        ProgramEntry {
+            path: PathBuf::from(path),
+            name: "app".to_string(),
+            display_name: "App".to_string(),
+            base_name: None,
+            source,
+            icon_path: None,
+            target: target.map(PathBuf::from),
+            target_name: None,
+            modified: None,
+            opener: None,
+        }
+    }
+
+    #[test]
+    fn start_menu_shortcuts_act_on_their_target() {
+        let app = entry(ProgramSource::StartMenu, "Start Menu/App.lnk", Some("C:/Apps/app.exe"));
+        let folder = entry(ProgramSource::StartMenu, "Start Menu/Docs.lnk", Some("C:/Users/me/Docs"));
+        let link = entry(ProgramSource::StartMenu, "Start Menu/Help.lnk", Some("C:/Apps/help.url"));
+        let settings = entry(ProgramSource::StartMenu, "Start Menu/Display.lnk", Some("C:/Windows/desk.cpl"));
+        assert_eq!(ResultAction::for_entry(&app), ResultAction::Launch);
+        assert_eq!(ResultAction::for_entry(&folder), ResultAction::RevealFolder);
+        assert_eq!(ResultAction::for_entry(&link), ResultAction::OpenUri);
+        assert_eq!(ResultAction::for_entry(&settings), ResultAction::Launch);
+    }
+
+    #[test]
+    fn internet_shortcuts_open_their_link() {
+        for source in [ProgramSource::StartMenu, ProgramSource::Desktop] {
+            let url = entry(source.clone(), "Desktop/Docs.URL", None);
+            let website = entry(source, "Desktop/Mail.website", None);
+            assert_eq!(ResultAction::for_entry(&url), ResultAction::OpenUri);
+            assert_eq!(ResultAction::for_entry(&website), ResultAction::OpenUri);
+        }
+    }
+
+    #[test]
+    fn unresolved_shortcuts_and_files_launch() {
+        let shortcut = entry(ProgramSource::Desktop, "Desktop/Broken.lnk", None);
+        let program = entry(ProgramSource::ProgramFiles, "C:/Program Files/App/app.exe", None);
+        let download = entry(ProgramSource::Desktop, "Downloads/notes.txt", None);
+        assert_eq!(ResultAction::for_entry(&shortcut), ResultAction::Launch);
+        assert_eq!(ResultAction::for_entry(&program), ResultAction::Launch);
+        assert_eq!(ResultAction::for_entry(&download), ResultAction::Launch);
+    }
+}
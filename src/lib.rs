@@ -0,0 +1,8 @@
+//! Indexing, search and configuration, without the UI, so benchmarks can
+//! use them. `launcher` and `platform` come along as their dependencies.
+
+pub mod config;
+pub mod indexer;
+pub mod launcher;
+pub mod platform;
+pub mod search;
@@ -1,30 +1,67 @@
 // Hide console window in release builds
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod config;
-mod indexer;
-mod platform;
-mod search;
+mod cli;
+mod i18n;
+mod popup;
+mod providers;
 mod ui;
 
-use config::Config;
+use locksearch::{config, indexer, launcher, platform, search};
+
+use config::{Config, WindowChrome, MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH};
 use iced::Application;
 use ui::App;
 
 fn main() -> iced::Result {
+    // Debug builds log at info level by default; release builds stay quiet
+    // unless RUST_LOG is set
+    let default_filter = if cfg!(debug_assertions) { "info" } else { "off" };
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter)).init();
+
     let config = Config::load();
 
-    // Spawn background thread to add WS_THICKFRAME for resize borders
-    // after iced/winit creates the frameless window
-    platform::setup_frameless_resize();
-    
+    let mode = cli::Mode::from_args(std::env::args().skip(1));
+    if !matches!(mode, Ok(cli::Mode::Gui)) {
+        // Release builds have no console of their own to print to
+        platform::attach_parent_console();
+    }
+    let mode = match mode {
+        Ok(mode) => mode,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if !matches!(mode, cli::Mode::Gui) {
+        std::process::exit(cli::run(mode, &config));
+    }
+    // A launcher hidden on focus loss is brought back instead of opening another
+    if platform::show_running_instance() {
+        return Ok(());
+    }
+
+    let native_chrome = config.window_chrome == WindowChrome::Native;
+    if !native_chrome {
+        // Spawn background thread to add WS_THICKFRAME for resize borders
+        // after iced/winit creates the frameless window
+        platform::setup_frameless_resize();
+    }
+    // Started from a hotkey, the window could otherwise open behind the active one
+    platform::force_foreground();
+
+    // iced sizes in DPI-scaled units; shrinking them back gives raw pixels
+    let scale = ui::ui_scale(&config) as f32;
     App::run(iced::Settings {
         window: iced::window::Settings {
-            size: iced::Size::new(config.window_width, config.window_height),
-            min_size: Some(iced::Size::new(400.0, 300.0)),
-            decorations: true,
+            size: iced::Size::new(config.window_width * scale, config.window_height * scale),
+            min_size: Some(iced::Size::new(MIN_WINDOW_WIDTH * scale, MIN_WINDOW_HEIGHT * scale)),
+            // Windows starts decorated for the resize borders and strips the
+            // caption afterwards; elsewhere frameless means no decorations
+            decorations: native_chrome || cfg!(target_os = "windows"),
             transparent: false,
             resizable: true,
+            level: ui::window_level(config.always_on_top),
             ..Default::default()
         },
         default_font: iced::Font::DEFAULT,
@@ -1,3 +1,6 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
 /// Windows-specific platform code for frameless window with resize support.
 ///
 /// Strategy: Start with `decorations: true` (gives native WS_THICKFRAME resize
@@ -17,13 +20,9 @@ pub fn setup_frameless_resize() {
             for attempt in 0..15 {
                 thread::sleep(Duration::from_millis(if attempt == 0 { 400 } else { 200 }));
 
-                // Find our window by its title
-                let title: Vec<u16> = "LockSearch\0".encode_utf16().collect();
-                let hwnd = FindWindowW(std::ptr::null(), title.as_ptr());
-
-                if hwnd.is_null() {
+                let Some(hwnd) = find_main_window() else {
                     continue;
-                }
+                };
 
                 let style = GetWindowLongW(hwnd, GWL_STYLE);
 
@@ -55,7 +54,439 @@ pub fn setup_frameless_resize() {
     });
 }
 
+/// The launcher's main window: the visible, resizable top-level window owned
+/// by this process. iced doesn't hand out its HWND, and matching by process
+/// can't pick up another app's window the way a title lookup could. winit's
+/// hidden helper windows fail the visibility and style checks.
+#[cfg(target_os = "windows")]
+fn find_main_window() -> Option<windows_sys::Win32::Foundation::HWND> {
+    use windows_sys::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows_sys::Win32::System::Threading::GetCurrentProcessId;
+    use windows_sys::Win32::UI::WindowsAndMessaging::*;
+
+    unsafe extern "system" fn check(hwnd: HWND, found: LPARAM) -> BOOL {
+        let mut pid = 0;
+        GetWindowThreadProcessId(hwnd, &mut pid);
+        let resizable = GetWindowLongW(hwnd, GWL_STYLE) & WS_THICKFRAME as i32 != 0;
+        if pid == GetCurrentProcessId() && IsWindowVisible(hwnd) != 0 && resizable {
+            *(found as *mut Option<HWND>) = Some(hwnd);
+            return 0; // Stop enumerating
+        }
+        1
+    }
+
+    let mut found: Option<HWND> = None;
+    unsafe {
+        EnumWindows(Some(check), &mut found as *mut Option<HWND> as LPARAM);
+    }
+    found
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn setup_frameless_resize() {
     // No-op on non-Windows platforms
 }
+
+/// Display scale of the system (1.5 at 150%), as set when the user signed in
+#[cfg(target_os = "windows")]
+pub fn system_scale_factor() -> f64 {
+    let dpi = unsafe { windows_sys::Win32::UI::HiDpi::GetDpiForSystem() };
+    if dpi == 0 { 1.0 } else { f64::from(dpi) / 96.0 }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn system_scale_factor() -> f64 {
+    1.0
+}
+
+/// Raise the launcher above other windows once it appears. The foreground
+/// lock refuses `SetForegroundWindow` to a process that didn't get the last
+/// input (e.g. one started from a hotkey), which leaves the window behind
+/// whatever was active.
+#[cfg(target_os = "windows")]
+pub fn force_foreground() {
+    use std::thread;
+    use std::time::Duration;
+
+    thread::spawn(|| {
+        // Wait for winit to create and show the window, as for the resize setup
+        for attempt in 0..15 {
+            thread::sleep(Duration::from_millis(if attempt == 0 { 400 } else { 200 }));
+            if let Some(hwnd) = find_main_window() {
+                unsafe { raise_window(hwnd) };
+                return;
+            }
+        }
+    });
+}
+
+/// The usual workaround for the foreground lock: attach to the input of the
+/// foreground window's thread, which may then hand over the foreground
+#[cfg(target_os = "windows")]
+unsafe fn raise_window(hwnd: windows_sys::Win32::Foundation::HWND) {
+    use windows_sys::Win32::System::Threading::AttachThreadInput;
+    use windows_sys::Win32::UI::WindowsAndMessaging::*;
+
+    let foreground_thread = GetWindowThreadProcessId(GetForegroundWindow(), std::ptr::null_mut());
+    let window_thread = GetWindowThreadProcessId(hwnd, std::ptr::null_mut());
+    let attached = foreground_thread != 0
+        && foreground_thread != window_thread
+        && AttachThreadInput(window_thread, foreground_thread, 1) != 0;
+
+    if IsIconic(hwnd) != 0 {
+        ShowWindow(hwnd, SW_RESTORE);
+    }
+    BringWindowToTop(hwnd);
+    SetForegroundWindow(hwnd);
+
+    if attached {
+        AttachThreadInput(window_thread, foreground_thread, 0);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn force_foreground() {
+    // Other platforms let a newly shown window take focus
+}
+
+/// Print to the console the launcher was started from, if any. Release
+/// builds use the GUI subsystem and get no console, so the command-line
+/// modes would otherwise print nothing.
+#[cfg(target_os = "windows")]
+pub fn attach_parent_console() {
+    use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    // Fails harmlessly when started without a console, or when the process
+    // already has one (debug builds)
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn attach_parent_console() {
+    // Other platforms keep the terminal's output streams
+}
+
+/// Named event a later launch sets to bring back the running launcher's
+/// hidden window (see `hide_on_focus_loss`)
+#[cfg(target_os = "windows")]
+const SHOW_EVENT: &str = "Local\\LockSearch.Show";
+
+/// Ask an already running launcher to show its window. Returns false when
+/// none is running, so this launch should open a window of its own.
+#[cfg(target_os = "windows")]
+pub fn show_running_instance() -> bool {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::Threading::{OpenEventW, SetEvent, EVENT_MODIFY_STATE};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{AllowSetForegroundWindow, ASFW_ANY};
+
+    let name: Vec<u16> = SHOW_EVENT.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        let event = OpenEventW(EVENT_MODIFY_STATE, 0, name.as_ptr());
+        if event.is_null() {
+            return false;
+        }
+        // This launch got the input (e.g. from a hotkey); let the running one take the foreground
+        AllowSetForegroundWindow(ASFW_ANY);
+        let signaled = SetEvent(event) != 0;
+        CloseHandle(event);
+        signaled
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn show_running_instance() -> bool {
+    false
+}
+
+/// Block until a later launch asks for the window (see `show_running_instance`).
+/// The first call makes this process the one that is asked. Returns false if
+/// that isn't possible, after which waiting again is pointless.
+#[cfg(target_os = "windows")]
+pub fn wait_for_show_request() -> bool {
+    use std::sync::OnceLock;
+    use windows_sys::Win32::Foundation::WAIT_OBJECT_0;
+    use windows_sys::Win32::System::Threading::{CreateEventW, WaitForSingleObject, INFINITE};
+
+    // The handle as an integer, which can live in a static
+    static EVENT: OnceLock<usize> = OnceLock::new();
+    let event = *EVENT.get_or_init(|| {
+        let name: Vec<u16> = SHOW_EVENT.encode_utf16().chain(std::iter::once(0)).collect();
+        // Auto-reset, so each request wakes a single wait
+        unsafe { CreateEventW(std::ptr::null(), 0, 0, name.as_ptr()) as usize }
+    });
+    event != 0 && unsafe { WaitForSingleObject(event as _, INFINITE) } == WAIT_OBJECT_0
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn wait_for_show_request() -> bool {
+    false
+}
+
+/// Flash the launcher's window border a couple of times
+#[cfg(target_os = "windows")]
+pub fn flash_window() {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{FlashWindowEx, FLASHWINFO, FLASHW_CAPTION};
+
+    let Some(hwnd) = find_main_window() else {
+        return;
+    };
+    let info = FLASHWINFO {
+        cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+        hwnd,
+        dwFlags: FLASHW_CAPTION,
+        uCount: 2,
+        // The caret blink rate
+        dwTimeout: 0,
+    };
+    unsafe { FlashWindowEx(&info) };
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn flash_window() {
+    // No-op on non-Windows platforms
+}
+
+/// Play the system's error sound
+#[cfg(target_os = "windows")]
+pub fn error_beep() {
+    use windows_sys::Win32::UI::WindowsAndMessaging::{MessageBeep, MB_ICONHAND};
+
+    unsafe { MessageBeep(MB_ICONHAND) };
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn error_beep() {
+    // No-op on non-Windows platforms
+}
+
+/// Launch a program elevated, showing the UAC prompt.
+#[cfg(target_os = "windows")]
+pub fn launch_elevated(path: &Path) -> io::Result<()> {
+    shell_execute("runas", path)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn launch_elevated(_path: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "running as administrator is only supported on Windows",
+    ))
+}
+
+/// Run a program as another account through `runas.exe`, which asks for the
+/// password in a console window of its own. Only the program itself runs;
+/// there is no way to pass a shortcut's arguments along.
+#[cfg(target_os = "windows")]
+pub fn launch_as_user(path: &Path, user: &str) -> io::Result<()> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NEW_CONSOLE: u32 = 0x0000_0010;
+
+    if user.contains('"') {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "user names can't contain quotes"));
+    }
+    // runas takes the command as one argument, itself quoted for paths with spaces
+    std::process::Command::new("runas")
+        .raw_arg(format!("\"/user:{}\"", user))
+        .raw_arg(format!("\"\\\"{}\\\"\"", path.display()))
+        .creation_flags(CREATE_NEW_CONSOLE)
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn launch_as_user(_path: &Path, _user: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "running as a different user is only supported on Windows",
+    ))
+}
+
+/// Open the folder containing `path`, with the item selected where supported.
+#[cfg(target_os = "windows")]
+pub fn reveal_in_folder(path: &Path) -> io::Result<()> {
+    let mut select = std::ffi::OsString::from("/select,");
+    select.push(path.as_os_str());
+    std::process::Command::new("explorer").arg(select).spawn().map(|_| ())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn reveal_in_folder(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(dir) => open::that(dir),
+        None => open::that(path),
+    }
+}
+
+/// Whether an entry is a reparse point (junction, symlink, mount point).
+/// `metadata` must not follow links, e.g. from `symlink_metadata`.
+#[cfg(target_os = "windows")]
+pub fn is_reparse_point(metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    use windows_sys::Win32::Storage::FileSystem::FILE_ATTRIBUTE_REPARSE_POINT;
+
+    metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_reparse_point(metadata: &std::fs::Metadata) -> bool {
+    metadata.file_type().is_symlink()
+}
+
+/// Paths this long need the `\\?\` prefix for Win32 file APIs
+#[cfg(target_os = "windows")]
+const MAX_PATH: usize = 260;
+
+/// Prefix an absolute drive path with `\\?\` so Win32 calls accept it (and
+/// everything below it) beyond `MAX_PATH`.
+#[cfg(target_os = "windows")]
+pub fn extended_length_path(path: &Path) -> PathBuf {
+    use std::path::{Component, Prefix};
+
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) if matches!(prefix.kind(), Prefix::Disk(_)) => {
+            let mut extended = std::ffi::OsString::from(r"\\?\");
+            extended.push(path.as_os_str());
+            PathBuf::from(extended)
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn extended_length_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// A form of `path` that file APIs and the shell can open: the plain path
+/// when it is short enough, the `\\?\` form when it is too long.
+#[cfg(target_os = "windows")]
+pub fn usable_path(path: &Path) -> PathBuf {
+    let plain = match path.to_str().and_then(|p| p.strip_prefix(r"\\?\")) {
+        // Only drive paths; `\\?\UNC\...` stays as it is
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => Path::new(rest),
+        _ => path,
+    };
+    if plain.as_os_str().len() < MAX_PATH {
+        plain.to_path_buf()
+    } else {
+        extended_length_path(plain)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn usable_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Start Menu "Programs" folders (all users, then current user) as reported
+/// by the shell's known-folder API, `None` for one it can't resolve.
+#[cfg(target_os = "windows")]
+pub fn start_menu_program_dirs() -> [Option<PathBuf>; 2] {
+    use windows_sys::Win32::UI::Shell::{FOLDERID_CommonPrograms, FOLDERID_Programs};
+
+    [known_folder(&FOLDERID_CommonPrograms), known_folder(&FOLDERID_Programs)]
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_menu_program_dirs() -> [Option<PathBuf>; 2] {
+    [None, None]
+}
+
+/// Program Files folders (native, then 32-bit) as reported by the shell's
+/// known-folder API, `None` for one it can't resolve
+#[cfg(target_os = "windows")]
+pub fn program_files_dirs() -> [Option<PathBuf>; 2] {
+    use windows_sys::Win32::UI::Shell::{FOLDERID_ProgramFiles, FOLDERID_ProgramFilesX86};
+
+    [known_folder(&FOLDERID_ProgramFiles), known_folder(&FOLDERID_ProgramFilesX86)]
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn program_files_dirs() -> [Option<PathBuf>; 2] {
+    [None, None]
+}
+
+/// The Desktop shared by all users
+#[cfg(target_os = "windows")]
+pub fn public_desktop_dir() -> Option<PathBuf> {
+    known_folder(&windows_sys::Win32::UI::Shell::FOLDERID_PublicDesktop)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn public_desktop_dir() -> Option<PathBuf> {
+    None
+}
+
+/// The user's display language as a locale name, e.g. "de-DE"
+#[cfg(target_os = "windows")]
+pub fn user_language() -> Option<String> {
+    use windows_sys::Win32::Globalization::GetUserDefaultLocaleName;
+
+    // LOCALE_NAME_MAX_LENGTH
+    let mut buffer = [0u16; 85];
+    // Returns the length including the terminating null, or 0 on failure
+    let len = unsafe { GetUserDefaultLocaleName(buffer.as_mut_ptr(), buffer.len() as i32) };
+    (len > 1).then(|| String::from_utf16_lossy(&buffer[..len as usize - 1]))
+}
+
+/// The user's display language from the usual locale variables, e.g. "de_DE.UTF-8"
+#[cfg(not(target_os = "windows"))]
+pub fn user_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+}
+
+/// Resolve a known folder id (`FOLDERID_*`) to its path.
+#[cfg(target_os = "windows")]
+fn known_folder(id: &windows_sys::core::GUID) -> Option<PathBuf> {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::OsStringExt;
+    use windows_sys::Win32::System::Com::CoTaskMemFree;
+    use windows_sys::Win32::UI::Shell::SHGetKnownFolderPath;
+
+    unsafe {
+        let mut raw: windows_sys::core::PWSTR = std::ptr::null_mut();
+        let hr = SHGetKnownFolderPath(id, 0, std::ptr::null_mut(), &mut raw);
+        let path = if hr >= 0 && !raw.is_null() {
+            let len = (0..).take_while(|&i| *raw.add(i) != 0).count();
+            Some(PathBuf::from(OsString::from_wide(std::slice::from_raw_parts(raw, len))))
+        } else {
+            None
+        };
+        // The buffer must be freed even when the call fails
+        CoTaskMemFree(raw as *const _);
+        path
+    }
+}
+
+/// Run `ShellExecuteW` with the given verb on a file.
+#[cfg(target_os = "windows")]
+fn shell_execute(verb: &str, path: &Path) -> io::Result<()> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::UI::Shell::ShellExecuteW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let verb: Vec<u16> = verb.encode_utf16().chain(std::iter::once(0)).collect();
+    let file: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+
+    // Values of 32 or below are error codes
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            verb.as_ptr(),
+            file.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    if result as isize > 32 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
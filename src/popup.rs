@@ -0,0 +1,264 @@
+//! A widget with a popup drawn on top of the rest of the window, anchored
+//! below it (or above, when the window has no room left below). Unlike a
+//! widget pushed into a column, opening the popup doesn't move anything else.
+//! The result list uses it for the right-click menu.
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::overlay;
+use iced::advanced::renderer;
+use iced::advanced::widget::{self, Tree, Widget};
+use iced::advanced::{Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::mouse;
+use iced::{Element, Length, Point, Rectangle, Size, Vector};
+
+/// `content` with `popup` open over it. A click anywhere outside the popup
+/// publishes `on_dismiss` and is not passed on to what is under the cursor.
+pub struct Popup<'a, Message, Theme, Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    popup: Element<'a, Message, Theme, Renderer>,
+    on_dismiss: Message,
+}
+
+impl<'a, Message, Theme, Renderer> Popup<'a, Message, Theme, Renderer> {
+    pub fn new(
+        content: impl Into<Element<'a, Message, Theme, Renderer>>,
+        popup: impl Into<Element<'a, Message, Theme, Renderer>>,
+        on_dismiss: Message,
+    ) -> Self {
+        Self { content: content.into(), popup: popup.into(), on_dismiss }
+    }
+}
+
+impl<'a, Message, Theme, Renderer> Widget<Message, Theme, Renderer> for Popup<'a, Message, Theme, Renderer>
+where
+    Message: Clone,
+    Renderer: renderer::Renderer,
+{
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn size_hint(&self) -> Size<Length> {
+        self.content.as_widget().size_hint()
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content), Tree::new(&self.popup)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&[self.content.as_widget(), self.popup.as_widget()]);
+    }
+
+    fn layout(&self, tree: &mut Tree, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.as_widget().layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(&tree.children[0], renderer, theme, style, layout, cursor, viewport);
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn widget::Operation<Message>,
+    ) {
+        self.content.as_widget().operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(&tree.children[0], layout, cursor, viewport, renderer)
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        let [content_tree, popup_tree] = &mut tree.children[..] else {
+            return None;
+        };
+        let mut overlays = overlay::Group::new();
+        if let Some(content) = self.content.as_widget_mut().overlay(content_tree, layout, renderer, translation) {
+            overlays = overlays.push(content);
+        }
+        overlays = overlays.push(overlay::Element::new(Box::new(PopupOverlay {
+            anchor: layout.bounds() + translation,
+            popup: &mut self.popup,
+            tree: popup_tree,
+            on_dismiss: self.on_dismiss.clone(),
+        })));
+        Some(overlays.overlay())
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Popup<'a, Message, Theme, Renderer>> for Element<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: 'a,
+    Renderer: renderer::Renderer + 'a,
+{
+    fn from(popup: Popup<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(popup)
+    }
+}
+
+struct PopupOverlay<'a, 'b, Message, Theme, Renderer> {
+    /// Bounds of the content in window coordinates
+    anchor: Rectangle,
+    popup: &'b mut Element<'a, Message, Theme, Renderer>,
+    tree: &'b mut Tree,
+    on_dismiss: Message,
+}
+
+impl<'a, 'b, Message, Theme, Renderer> overlay::Overlay<Message, Theme, Renderer>
+    for PopupOverlay<'a, 'b, Message, Theme, Renderer>
+where
+    Message: Clone,
+    Renderer: renderer::Renderer,
+{
+    fn layout(&mut self, renderer: &Renderer, bounds: Size) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, bounds);
+        let node = self.popup.as_widget().layout(self.tree, renderer, &limits);
+        let position = popup_position(self.anchor, node.size(), bounds);
+        node.move_to(position)
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.popup.as_widget().draw(self.tree, renderer, theme, style, layout, cursor, &layout.bounds());
+    }
+
+    fn operate(&mut self, layout: Layout<'_>, renderer: &Renderer, operation: &mut dyn widget::Operation<Message>) {
+        self.popup.as_widget().operate(self.tree, layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        if let Event::Mouse(mouse::Event::ButtonPressed(_)) = event {
+            if !cursor.is_over(layout.bounds()) {
+                shell.publish(self.on_dismiss.clone());
+                return event::Status::Captured;
+            }
+        }
+        self.popup.as_widget_mut().on_event(
+            self.tree,
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &layout.bounds(),
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.popup.as_widget().mouse_interaction(self.tree, layout, cursor, viewport, renderer)
+    }
+}
+
+/// Top-left corner for a popup of `size` under `anchor`, left-aligned with it.
+/// Flips above the anchor when it doesn't fit below, and never leaves `window`.
+fn popup_position(anchor: Rectangle, size: Size, window: Size) -> Point {
+    let below = anchor.y + anchor.height;
+    let y = if below + size.height <= window.height || anchor.y < size.height {
+        below.min(window.height - size.height)
+    } else {
+        anchor.y - size.height
+    };
+    let x = anchor.x.min(window.width - size.width);
+    Point::new(x.max(0.0), y.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Size = Size { width: 500.0, height: 500.0 };
+    const MENU: Size = Size { width: 240.0, height: 200.0 };
+
+    fn row_at(y: f32) -> Rectangle {
+        Rectangle { x: 10.0, y, width: 480.0, height: 60.0 }
+    }
+
+    #[test]
+    fn opens_below_the_row() {
+        assert_eq!(popup_position(row_at(100.0), MENU, WINDOW), Point::new(10.0, 160.0));
+    }
+
+    #[test]
+    fn flips_above_a_row_near_the_bottom() {
+        assert_eq!(popup_position(row_at(400.0), MENU, WINDOW), Point::new(10.0, 200.0));
+    }
+
+    #[test]
+    fn stays_inside_a_window_too_short_for_either_side() {
+        let window = Size { width: 200.0, height: 300.0 };
+        let position = popup_position(row_at(120.0), MENU, window);
+        assert_eq!(position, Point::new(0.0, 100.0));
+    }
+}
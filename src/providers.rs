@@ -0,0 +1,168 @@
+//! Results from outside the program index. Each provider sees the query of
+//! every search and answers with ready-made rows, listed after the programs
+//! on screen (see [`merge`]). Launching one is not counted in the usage history.
+//!
+//! To add a source (e.g. turn "JIRA-123" into a ticket link), implement
+//! [`ResultProvider`] and push it in [`from_config`]. Results use
+//! `ProgramSource::Provider`; `ResultAction::OpenUri` with the URL as `path`
+//! opens it in the browser.
+
+use crate::config::Config;
+use crate::i18n::Strings;
+use crate::indexer::ProgramSource;
+use crate::launcher::ResultAction;
+use crate::search::SourceFilter;
+use crate::ui::ProgramResult;
+use std::path::PathBuf;
+
+/// A source of extra results. Called off the UI thread for each search, so
+/// it should answer quickly and without blocking on the network.
+pub trait ResultProvider: Send + Sync {
+    fn query(&self, q: &str) -> Vec<ProgramResult>;
+}
+
+/// Providers enabled by `config`, in the order their results are listed
+pub fn from_config(config: &Config, strings: &Strings) -> Vec<Box<dyn ResultProvider>> {
+    let mut providers: Vec<Box<dyn ResultProvider>> = Vec::new();
+    if let Some(url) = config.web_search_url.as_deref().filter(|url| url.contains("{query}")) {
+        providers.push(Box::new(WebSearch {
+            url: url.to_string(),
+            label: strings.search_web.clone(),
+        }));
+    } else if config.web_search_url.is_some() {
+        log::warn!("Ignoring web_search_url without a {{query}} placeholder");
+    }
+    providers
+}
+
+/// Rows of every provider for `query`, none when a leading operator (see
+/// `SourceFilter`) leaves out web results. Providers see the query without it.
+pub fn query_all(providers: &[Box<dyn ResultProvider>], query: &str) -> Vec<ProgramResult> {
+    let (filter, query) = SourceFilter::parse(query);
+    if filter.is_some_and(|filter| !filter.includes(&ProgramSource::Provider, None)) {
+        return Vec::new();
+    }
+    providers.iter().flat_map(|provider| provider.query(query)).collect()
+}
+
+/// `programs` with the `provided` rows placed last among the first `shown`
+/// rows, taking the place of the programs that would be listed there, which
+/// follow them for "Show more"
+pub fn merge(mut programs: Vec<ProgramResult>, provided: Vec<ProgramResult>, shown: usize) -> Vec<ProgramResult> {
+    let at = shown.saturating_sub(provided.len()).min(programs.len());
+    programs.splice(at..at, provided);
+    programs
+}
+
+/// "Search the web for …" as the last result of every search
+struct WebSearch {
+    /// Search URL with `{query}` where the query goes
+    url: String,
+    /// Row text with `{query}` where the query goes
+    label: String,
+}
+
+impl ResultProvider for WebSearch {
+    fn query(&self, q: &str) -> Vec<ProgramResult> {
+        let q = q.trim();
+        // Folder paths are navigated, not searched for
+        if q.is_empty() || q.ends_with(['\\', '/']) {
+            return Vec::new();
+        }
+        vec![ProgramResult {
+            path: PathBuf::from(self.url.replace("{query}", &percent_encode(q))),
+            display_name: self.label.replace("{query}", q),
+            base_name: None,
+            icon_path: None,
+            icon_ready: false,
+            source: ProgramSource::Provider,
+            extension: Some("url".to_string()),
+            target: None,
+            pinned: false,
+            opener: None,
+            action: ResultAction::OpenUri,
+            is_new: false,
+            match_indices: Vec::new(),
+            exists: true,
+        }]
+    }
+}
+
+/// `text` escaped for a URL query value (RFC 3986 unreserved characters kept)
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for b in text.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(display_name: &str, source: ProgramSource) -> ProgramResult {
+        ProgramResult {
+            path: PathBuf::from(display_name),
+            display_name: display_name.to_string(),
+            base_name: None,
+            icon_path: None,
+            icon_ready: false,
+            source,
+            extension: None,
+            target: None,
+            pinned: false,
+            opener: None,
+            action: ResultAction::Launch,
+            is_new: false,
+            match_indices: Vec::new(),
+            exists: true,
+        }
+    }
+
+    fn programs(count: usize) -> Vec<ProgramResult> {
+        (0..count).map(|i| row(&format!("app{}", i), ProgramSource::StartMenu)).collect()
+    }
+
+    fn names(rows: &[ProgramResult]) -> Vec<&str> {
+        rows.iter().map(|r| r.display_name.as_str()).collect()
+    }
+
+    /// Answers every query with one row naming it
+    struct Echo;
+
+    impl ResultProvider for Echo {
+        fn query(&self, q: &str) -> Vec<ProgramResult> {
+            vec![row(&format!("echo {}", q), ProgramSource::Provider)]
+        }
+    }
+
+    #[test]
+    fn provided_rows_end_the_rows_on_screen() {
+        let merged = merge(programs(6), query_all(&[Box::new(Echo)], "app"), 3);
+        assert_eq!(names(&merged), ["app0", "app1", "echo app", "app2", "app3", "app4", "app5"]);
+    }
+
+    #[test]
+    fn provided_rows_follow_fewer_programs_than_fit() {
+        let merged = merge(programs(2), query_all(&[Box::new(Echo)], "app"), 5);
+        assert_eq!(names(&merged), ["app0", "app1", "echo app"]);
+    }
+
+    #[test]
+    fn operators_apply_to_providers() {
+        let providers: Vec<Box<dyn ResultProvider>> = vec![Box::new(Echo)];
+        assert_eq!(names(&query_all(&providers, "w: rust")), ["echo rust"]);
+        assert!(query_all(&providers, "a: rust").is_empty());
+    }
+
+    #[test]
+    fn queries_are_percent_encoded() {
+        assert_eq!(percent_encode("rust lang"), "rust%20lang");
+        assert_eq!(percent_encode("a&b=c/d?"), "a%26b%3Dc%2Fd%3F");
+        assert_eq!(percent_encode("Grüße-1.0_~"), "Gr%C3%BC%C3%9Fe-1.0_~");
+    }
+}
@@ -1,17 +1,177 @@
-use crate::indexer::ProgramEntry;
+use crate::config::{CaseSensitivity, Config, Tiebreaker};
+use crate::indexer::{ProgramEntry, ProgramSource, UsageMap};
+use crate::launcher::ResultAction;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Upper bound on the number of results a single search may return
+pub const MAX_RESULTS_LIMIT: usize = 500;
+
+/// Score bonus for entries the user has pinned
+const PINNED_BOOST: i64 = 200;
+
+/// Entries scored between checks for a superseded search
+const CANCEL_CHECK_INTERVAL: usize = 256;
+
+/// Score bonus per recorded launch of an entry
+const USAGE_BOOST_PER_LAUNCH: i64 = 10;
+
+/// Upper bound on the launch-count bonus, so history can't drown out relevance
+const MAX_USAGE_BOOST: i64 = 100;
+
+/// Target file name matches count for this fraction of a name match
+const TARGET_NAME_WEIGHT_DIVISOR: i64 = 2;
+
+/// Score bonus when the display name starts with the query
+const DISPLAY_PREFIX_BOOST: i64 = 100;
+
+/// Score bonus when only the file name starts with the query
+const NAME_PREFIX_BOOST: i64 = 75;
+
+/// Score bonus when the query's words match the folders of an entry's location
+const PATH_COMPONENT_BOOST: i64 = 40;
+
+/// Score bonus when the display or file name is the whole query, e.g.
+/// "notepad" for notepad.exe. Beats a prefix match with the source and
+/// full usage bonuses, so typing an exact name puts it first.
+const EXACT_NAME_BOOST: i64 = 300;
+
+/// Lowercase text for matching, dropping the U+FFFD replacement characters
+/// that `to_string_lossy` inserts for non-UTF8 path segments
+pub fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| *c != char::REPLACEMENT_CHARACTER)
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// `text` without the U+FFFD replacement characters, case kept
+fn strip_replacement(text: &str) -> String {
+    text.chars().filter(|c| *c != char::REPLACEMENT_CHARACTER).collect()
+}
+
+/// Original-case file stem of a path
+fn file_stem(path: &Path) -> String {
+    path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Bitmask of the ASCII letters and digits in lowercased `text`. Every query
+/// character must occur in a fuzzy match, so a candidate whose mask lacks a
+/// query bit can be skipped without running the (much slower) matcher.
+fn char_mask(text: &str) -> u64 {
+    text.bytes().fold(0, |mask, b| match b {
+        b'a'..=b'z' => mask | 1u64 << (b - b'a'),
+        b'0'..=b'9' => mask | 1u64 << (26 + b - b'0'),
+        _ => mask,
+    })
+}
+
+/// Kind of result a query is limited to by a leading operator, e.g.
+/// `s: bluetooth` for settings only
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceFilter {
+    /// `a:` programs from the Start Menu and Program Files
+    Apps,
+    /// `s:` Control Panel items and management consoles (`.cpl`, `.msc`)
+    Settings,
+    /// `f:` Desktop and Downloads shortcuts
+    Files,
+    /// `w:` internet shortcuts and web search results
+    Web,
+}
+
+impl SourceFilter {
+    /// The filter named by a leading `<token>:` and the query after it.
+    /// Unknown tokens and drive paths (`F:\Tools`) leave the query as typed.
+    pub fn parse(query: &str) -> (Option<SourceFilter>, &str) {
+        let trimmed = query.trim_start();
+        let Some((token, rest)) = trimmed.split_once(':') else {
+            return (None, query);
+        };
+        let filter = match token.to_ascii_lowercase().as_str() {
+            "a" => SourceFilter::Apps,
+            "s" => SourceFilter::Settings,
+            "f" => SourceFilter::Files,
+            "w" => SourceFilter::Web,
+            _ => return (None, query),
+        };
+        if rest.starts_with(['\\', '/']) {
+            return (None, query);
+        }
+        (Some(filter), rest.trim_start())
+    }
+
+    /// Whether a result from `source` with `extension` belongs to this filter
+    pub fn includes(&self, source: &ProgramSource, extension: Option<&str>) -> bool {
+        let is_settings = matches!(extension, Some("cpl" | "msc"));
+        let is_web = matches!(extension, Some("url" | "website")) || *source == ProgramSource::Provider;
+        match self {
+            SourceFilter::Settings => is_settings,
+            SourceFilter::Web => is_web,
+            SourceFilter::Apps => {
+                !is_settings && !is_web && matches!(source, ProgramSource::StartMenu | ProgramSource::ProgramFiles)
+            }
+            SourceFilter::Files => {
+                !is_settings && !is_web && matches!(source, ProgramSource::Desktop | ProgramSource::Filesystem)
+            }
+        }
+    }
+
+    fn includes_entry(&self, entry: &ProgramEntry) -> bool {
+        self.includes(&entry.source, entry.extension().as_deref())
+    }
+}
 
 /// Search result with score
 #[derive(Clone, Debug)]
 pub struct SearchResult {
     pub entry: ProgramEntry,
     pub score: i64,
+    /// Normalized display name, kept for breaking ties without re-normalizing
+    name_key: String,
+}
+
+/// Search result with what's needed to show and activate it
+#[derive(Clone, Debug)]
+pub struct RankedResult {
+    pub entry: ProgramEntry,
+    /// Character positions in the lowercased display name matched by the query;
+    /// empty for the idle list or when only another field matched
+    pub match_indices: Vec<usize>,
+    /// What activating the result does
+    pub action: ResultAction,
 }
 
 /// Fast fuzzy search engine for programs
 pub struct SearchEngine {
     matcher: SkimMatcherV2,
+    /// Minimum fuzzy score (before boosts) for an entry to match
+    min_score: i64,
+    /// Lowercased display names of pinned programs
+    pinned: HashSet<String>,
+    /// Lowercased display names of programs left out of results
+    hidden: HashSet<String>,
+    /// Shorter queries show the idle list instead of searching
+    min_query_length: usize,
+    /// Order the idle list newest first instead of alphabetically
+    idle_by_recency: bool,
+    /// Sources shown in the idle list; empty shows all
+    idle_sources: Vec<ProgramSource>,
+    /// List folder contents for queries like `C:\Tools\`
+    path_navigation: bool,
+    /// Match query words against the folders of an entry's location
+    path_components: bool,
+    /// Rank results source by source, see `search_grouped`
+    group_by_source: bool,
+    /// Order of equally scored results
+    tiebreaker: Tiebreaker,
+    /// Whether the matcher sees original case (it is configured to match)
+    case_sensitivity: CaseSensitivity,
 }
 
 impl Default for SearchEngine {
@@ -22,65 +182,780 @@ impl Default for SearchEngine {
 
 impl SearchEngine {
     pub fn new() -> Self {
+        Self::from_config(&Config::default())
+    }
+
+    pub fn from_config(config: &Config) -> Self {
         Self {
-            matcher: SkimMatcherV2::default(),
+            matcher: match config.case_sensitivity {
+                CaseSensitivity::Insensitive => SkimMatcherV2::default().ignore_case(),
+                CaseSensitivity::Sensitive => SkimMatcherV2::default().respect_case(),
+                CaseSensitivity::Smart => SkimMatcherV2::default().smart_case(),
+            },
+            min_score: config.min_score,
+            pinned: config.pinned.iter().map(|name| normalize(name)).collect(),
+            hidden: config.hidden_programs.iter().map(|name| normalize(name)).collect(),
+            min_query_length: config.min_query_length,
+            idle_by_recency: config.initial_sort.eq_ignore_ascii_case("recent"),
+            tiebreaker: config.tiebreaker,
+            case_sensitivity: config.case_sensitivity,
+            path_navigation: config.path_navigation,
+            path_components: config.path_component_matching,
+            group_by_source: config.group_by_source,
+            idle_sources: config.empty_query_sources.clone(),
         }
     }
 
-    /// Search through program entries
-    pub fn search(&self, query: &str, entries: &[ProgramEntry]) -> Vec<SearchResult> {
-        if query.is_empty() {
-            // Return first 20 programs when no query
-            return entries
+    fn is_pinned(&self, entry: &ProgramEntry) -> bool {
+        !self.pinned.is_empty() && entry.config_names().any(|name| self.pinned.contains(&normalize(name)))
+    }
+
+    fn is_hidden(&self, entry: &ProgramEntry) -> bool {
+        !self.hidden.is_empty() && entry.config_names().any(|name| self.hidden.contains(&normalize(name)))
+    }
+
+    /// Text as the matcher should see it: the already lowercased form when
+    /// case is ignored, else `original` (computed only then) as typed
+    fn cased<'a>(&self, lower: &'a str, original: impl FnOnce() -> String) -> Cow<'a, str> {
+        match self.case_sensitivity {
+            CaseSensitivity::Insensitive => Cow::Borrowed(lower),
+            CaseSensitivity::Sensitive | CaseSensitivity::Smart => Cow::Owned(strip_replacement(&original())),
+        }
+    }
+
+    fn launches(usage: &UsageMap, entry: &ProgramEntry) -> u32 {
+        usage.get(&entry.path).map_or(0, |stat| stat.launches)
+    }
+
+    /// Order two equally scored entries, given with their normalized display
+    /// names, per the configured tiebreaker. Ends with the path, so the order
+    /// never depends on the index's order.
+    fn break_tie(
+        &self,
+        (a, a_key): (&ProgramEntry, &str),
+        (b, b_key): (&ProgramEntry, &str),
+        usage: &UsageMap,
+    ) -> Ordering {
+        let by_launches = || Self::launches(usage, b).cmp(&Self::launches(usage, a));
+        let by_name = || a_key.cmp(b_key);
+        let by_length = || a.display_name.chars().count().cmp(&b.display_name.chars().count());
+
+        let order = match self.tiebreaker {
+            Tiebreaker::Usage => by_launches().then_with(by_name),
+            Tiebreaker::Alphabetical => by_name().then_with(by_length),
+            Tiebreaker::Shortest => by_length().then_with(by_name),
+        };
+        order.then_with(|| a.path.cmp(&b.path))
+    }
+
+    /// Search through program entries, returning at most `max_results` matches.
+    /// Frequently launched entries (per `usage`) rank higher. A leading
+    /// operator such as `s:` limits the search, see `SourceFilter`.
+    /// `is_cancelled` is polled while scoring; once it reports true (e.g. a
+    /// newer query superseded this one) the search stops and returns `None`.
+    pub fn search(
+        &self,
+        query: &str,
+        entries: &[ProgramEntry],
+        usage: &UsageMap,
+        max_results: usize,
+        is_cancelled: impl Fn() -> bool,
+    ) -> Option<Vec<SearchResult>> {
+        self.search_streaming(query, entries, usage, max_results, is_cancelled, |_| {})
+    }
+
+    /// `search`, calling `on_update` with the best matches so far after each
+    /// batch of entries that changed them. The best `max_results` are kept in
+    /// final order while scoring, so the last update is the returned list.
+    /// The idle list (no query) is returned without updates.
+    pub fn search_streaming(
+        &self,
+        query: &str,
+        entries: &[ProgramEntry],
+        usage: &UsageMap,
+        max_results: usize,
+        is_cancelled: impl Fn() -> bool,
+        mut on_update: impl FnMut(&[SearchResult]),
+    ) -> Option<Vec<SearchResult>> {
+        let limit = max_results.clamp(1, MAX_RESULTS_LIMIT);
+        let (filter, query) = SourceFilter::parse(query);
+        let is_excluded = |entry: &ProgramEntry| {
+            self.is_hidden(entry) || filter.is_some_and(|filter| !filter.includes_entry(entry))
+        };
+
+        if query.chars().count() < self.min_query_length.max(1) {
+            // Return the first programs when no (or too short a) query: pinned, then most launched
+            let mut idle: Vec<&ProgramEntry> = entries
                 .iter()
-                .take(20)
-                .map(|e| SearchResult {
-                    entry: e.clone(),
-                    score: 0,
-                })
+                .filter(|e| !is_excluded(e))
+                .filter(|e| self.idle_sources.is_empty() || self.idle_sources.contains(&e.source))
                 .collect();
+            if self.idle_by_recency {
+                idle.sort_by_key(|e| (!self.is_pinned(e), std::cmp::Reverse(e.modified)));
+            } else {
+                idle.sort_by_key(|e| (!self.is_pinned(e), std::cmp::Reverse(Self::launches(usage, e))));
+            }
+            return Some(
+                idle.into_iter()
+                    .take(limit)
+                    .map(|e| SearchResult {
+                        entry: e.clone(),
+                        score: 0,
+                        name_key: String::new(),
+                    })
+                    .collect(),
+            );
+        }
+
+        let query_lower = normalize(query);
+        if query_lower.is_empty() {
+            return Some(Vec::new());
         }
 
-        let query_lower = query.to_lowercase();
+        let query_text = self.cased(&query_lower, || query.to_string());
+        // Case-blind, so it holds in every case mode
+        let query_mask = char_mask(&query_lower);
+        let may_match = |text: &str| query_mask & !char_mask(text) == 0;
+
+        let mut best: Vec<SearchResult> = Vec::with_capacity(limit);
+        for chunk in entries.chunks(CANCEL_CHECK_INTERVAL) {
+            if is_cancelled() {
+                return None;
+            }
+            let scored = chunk.iter().filter(|entry| !is_excluded(entry)).filter_map(|entry| {
+                let display_lower = normalize(&entry.display_name);
+                let name_lower = normalize(&entry.name);
 
-        let mut results: Vec<SearchResult> = entries
-            .iter()
-            .filter_map(|entry| {
                 // Try matching against display name
-                let display_score = self.matcher.fuzzy_match(&entry.display_name.to_lowercase(), &query_lower);
+                let display_score = may_match(&display_lower)
+                    .then(|| {
+                        let display_text = self.cased(&display_lower, || entry.display_name.clone());
+                        self.matcher.fuzzy_match(&display_text, &query_text)
+                    })
+                    .flatten();
                 
                 // Try matching against file name
-                let name_score = self.matcher.fuzzy_match(&entry.name, &query_lower);
+                let name_score = may_match(&name_lower)
+                    .then(|| {
+                        let name_text = self.cased(&name_lower, || file_stem(&entry.path));
+                        self.matcher.fuzzy_match(&name_text, &query_text)
+                    })
+                    .flatten();
+
+                // Try matching against a shortcut's target file name, at reduced weight
+                let target_score = entry
+                    .target_name
+                    .as_deref()
+                    .filter(|target| may_match(target))
+                    .and_then(|target| {
+                        let target_lower = normalize(target);
+                        let target_text = self.cased(&target_lower, || {
+                            entry.target.as_deref().map(file_stem).unwrap_or_default()
+                        });
+                        self.matcher.fuzzy_match(&target_text, &query_text)
+                    })
+                    .map(|score| score / TARGET_NAME_WEIGHT_DIVISOR);
+
+                let path_match = self.path_components && {
+                    let location = entry.target.as_deref().unwrap_or(&entry.path);
+                    matches_path_components(&query_lower, location)
+                };
 
-                // Take the best score
-                let base_score = display_score.max(name_score)?;
+                // Take the best score, dropping weak matches. A folder match
+                // alone still counts, with nothing but its boost.
+                let base_score = match display_score.max(name_score).max(target_score) {
+                    Some(score) if score < self.min_score && !path_match => return None,
+                    Some(score) => score,
+                    None if path_match => 0,
+                    None => return None,
+                };
+                let path_boost = if path_match { PATH_COMPONENT_BOOST } else { 0 };
 
                 // Boost Start Menu items
                 let source_boost = match entry.source {
-                    crate::indexer::ProgramSource::StartMenu => 50,
-                    crate::indexer::ProgramSource::ProgramFiles => 0,
+                    ProgramSource::StartMenu => 50,
+                    ProgramSource::Desktop => 50,
+                    ProgramSource::ProgramFiles | ProgramSource::Filesystem | ProgramSource::Provider => 0,
                 };
 
-                // Boost exact prefix matches
-                let prefix_boost = if entry.display_name.to_lowercase().starts_with(&query_lower) {
-                    100
+                // Boost exact names, then exact prefix matches, e.g. "wt" for wt.exe
+                let prefix_boost = if display_lower == query_lower || name_lower == query_lower {
+                    EXACT_NAME_BOOST
+                } else if display_lower.starts_with(&query_lower) {
+                    DISPLAY_PREFIX_BOOST
+                } else if name_lower.starts_with(&query_lower) {
+                    NAME_PREFIX_BOOST
                 } else {
                     0
                 };
 
-                Some(SearchResult {
-                    entry: entry.clone(),
-                    score: base_score + source_boost + prefix_boost,
-                })
+                let pinned_boost = if self.is_pinned(entry) { PINNED_BOOST } else { 0 };
+
+                let usage_boost =
+                    (Self::launches(usage, entry) as i64 * USAGE_BOOST_PER_LAUNCH).min(MAX_USAGE_BOOST);
+
+                let score = base_score + source_boost + prefix_boost + path_boost + pinned_boost + usage_boost;
+                Some((score, entry, display_lower))
+            });
+
+            let mut changed = false;
+            for (score, entry, name_key) in scored {
+                changed |= self.keep_if_best(&mut best, score, entry, name_key, limit, usage);
+            }
+            if changed {
+                on_update(&best);
+            }
+        }
+
+        Some(best)
+    }
+
+    /// Insert a match, with its normalized display name, into `best` (sorted
+    /// by score descending, then the tiebreaker) if it is among the top
+    /// `limit`. Only those are cloned.
+    fn keep_if_best(
+        &self,
+        best: &mut Vec<SearchResult>,
+        score: i64,
+        entry: &ProgramEntry,
+        name_key: String,
+        limit: usize,
+        usage: &UsageMap,
+    ) -> bool {
+        let ranks_before = |other: &SearchResult| {
+            let tie = || self.break_tie((entry, &name_key), (&other.entry, &other.name_key), usage);
+            score.cmp(&other.score).reverse().then_with(tie) == Ordering::Less
+        };
+        if best.len() >= limit && !best.last().is_some_and(ranks_before) {
+            return false;
+        }
+        let position = best.partition_point(|other| !ranks_before(other));
+        best.insert(position, SearchResult { entry: entry.clone(), score, name_key });
+        best.truncate(limit);
+        true
+    }
+
+    /// `search`, with the results bucketed by source. Each bucket keeps the
+    /// order of the flat search, and the map iterates in source priority
+    /// order (Start Menu first), matching the grouped view's sections.
+    pub fn search_grouped(
+        &self,
+        query: &str,
+        entries: &[ProgramEntry],
+        usage: &UsageMap,
+        max_results: usize,
+        is_cancelled: impl Fn() -> bool,
+    ) -> Option<BTreeMap<ProgramSource, Vec<SearchResult>>> {
+        let mut groups: BTreeMap<ProgramSource, Vec<SearchResult>> = BTreeMap::new();
+        for result in self.search(query, entries, usage, max_results, is_cancelled)? {
+            groups.entry(result.entry.source.clone()).or_default().push(result);
+        }
+        Some(groups)
+    }
+
+    /// Like `search`, with match positions and action filled in for each
+    /// result. With path navigation on, a folder path query lists that folder
+    /// instead. This touches the filesystem, so call it off the UI thread.
+    /// `on_update` gets the best matches so far, see `search_streaming`; the
+    /// grouped view gets none, as its sections would reshuffle with each one.
+    pub fn rank(
+        &self,
+        query: &str,
+        entries: &[ProgramEntry],
+        usage: &UsageMap,
+        max_results: usize,
+        is_cancelled: impl Fn() -> bool,
+        on_update: impl FnMut(&[SearchResult]),
+    ) -> Option<Vec<RankedResult>> {
+        if let Some(dir) = navigation_dir(query).filter(|_| self.path_navigation) {
+            return Some(list_directory(&dir, max_results));
+        }
+
+        let results = if self.group_by_source {
+            // Sections in order, so selection indices follow the rendered rows
+            self.search_grouped(query, entries, usage, max_results, is_cancelled)?
+                .into_values()
+                .flatten()
+                .collect()
+        } else {
+            self.search_streaming(query, entries, usage, max_results, is_cancelled, on_update)?
+        };
+        Some(self.ranked(query, results))
+    }
+
+    /// `results` of `query` with match positions and action filled in
+    pub fn ranked(&self, query: &str, results: Vec<SearchResult>) -> Vec<RankedResult> {
+        // Highlight what was matched, not the operator
+        let (_, query) = SourceFilter::parse(query);
+        let query_lower = normalize(query);
+        let query_text = self.cased(&query_lower, || query.to_string());
+        let searched = query.chars().count() >= self.min_query_length.max(1);
+
+        results
+            .into_iter()
+            .map(|r| {
+                let match_indices = if searched {
+                    let display_lower = normalize(&r.entry.display_name);
+                    let display_text = self.cased(&display_lower, || r.entry.display_name.clone());
+                    self.matcher
+                        .fuzzy_indices(&display_text, &query_text)
+                        .map(|(_, indices)| indices)
+                        .unwrap_or_default()
+                } else {
+                    Vec::new()
+                };
+                RankedResult {
+                    action: ResultAction::for_entry(&r.entry),
+                    match_indices,
+                    entry: r.entry,
+                }
             })
+            .collect()
+    }
+}
+
+/// Whether each word of a query of two or more words starts a component of
+/// `path`, or the initials of one, in order: "jb ij" matches
+/// `C:\Program Files\JetBrains\IntelliJ IDEA\bin\idea64.exe`. Single
+/// words are left to the name match; "c" alone would match every drive path.
+fn matches_path_components(query_lower: &str, path: &Path) -> bool {
+    path_component_matches(query_lower, path).is_some()
+}
+
+/// Where `matches_path_components` finds each word: positions among the
+/// folder and file names of `path` (drive and root left out), or `None`
+/// when it doesn't match
+pub fn path_component_matches(query_lower: &str, path: &Path) -> Option<Vec<usize>> {
+    let words: Vec<&str> = query_lower.split_whitespace().collect();
+    if words.len() < 2 {
+        return None;
+    }
+    let mut components = path_names(path).enumerate();
+    words
+        .iter()
+        .map(|word| components.find(|(_, component)| starts_component(word, component)).map(|(i, _)| i))
+        .collect()
+}
+
+/// Folder and file names of `path`, without its drive or root
+pub fn path_names(path: &Path) -> impl Iterator<Item = Cow<'_, str>> {
+    path.components().filter_map(|component| match component {
+        Component::Normal(name) => Some(name.to_string_lossy()),
+        _ => None,
+    })
+}
+
+/// Whether a lowercased query word starts `component` or its initials
+fn starts_component(word: &str, component: &str) -> bool {
+    normalize(component).starts_with(word) || initials(component).starts_with(word)
+}
+
+/// Lowercased first letters of the words in a name, which start after a
+/// separator or where lowercase turns uppercase: "jb" for "JetBrains",
+/// "iji" for "IntelliJ IDEA"
+fn initials(name: &str) -> String {
+    let mut initials = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        let starts_word = c.is_alphanumeric()
+            && previous.is_none_or(|p| !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()));
+        if starts_word {
+            initials.extend(c.to_lowercase());
+        }
+        previous = Some(c);
+    }
+    initials
+}
+
+/// The folder a query like `C:\Tools\` points at, if it exists
+fn navigation_dir(query: &str) -> Option<PathBuf> {
+    let query = query.trim();
+    if !query.ends_with(['\\', '/']) {
+        return None;
+    }
+    let dir = PathBuf::from(query);
+    dir.is_dir().then_some(dir)
+}
+
+/// Immediate children of `dir` as results: folders first (activating one
+/// navigates into it), then files, each alphabetical
+fn list_directory(dir: &Path, max_results: usize) -> Vec<RankedResult> {
+    let children = match fs::read_dir(dir) {
+        Ok(children) => children,
+        Err(e) => {
+            log::debug!("Cannot list {}: {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut listed: Vec<(bool, ProgramEntry)> = children
+        .filter_map(|child| child.ok())
+        .map(|child| {
+            let path = child.path();
+            // Follows junctions and links, unlike the entry's own file type
+            let is_dir = path.is_dir();
+            let display_name = child.file_name().to_string_lossy().into_owned();
+            let name = path
+                .file_stem()
+                .map(|stem| normalize(&stem.to_string_lossy()))
+                .unwrap_or_default();
+            let entry = ProgramEntry {
+                path,
+                name,
+                display_name,
+                base_name: None,
+                source: ProgramSource::Filesystem,
+                icon_path: None,
+                target: None,
+                target_name: None,
+                modified: None,
+                opener: None,
+            };
+            (is_dir, entry)
+        })
+        .collect();
+    listed.sort_by_cached_key(|(is_dir, entry)| (!is_dir, normalize(&entry.display_name)));
+    listed.truncate(max_results.clamp(1, MAX_RESULTS_LIMIT));
+
+    listed
+        .into_iter()
+        .map(|(is_dir, entry)| RankedResult {
+            action: if is_dir { ResultAction::Navigate } else { ResultAction::for_entry(&entry) },
+            match_indices: Vec::new(),
+            entry,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leading_operators_pick_a_filter() {
+        assert_eq!(SourceFilter::parse("s: bluetooth"), (Some(SourceFilter::Settings), "bluetooth"));
+        assert_eq!(SourceFilter::parse("  A:code"), (Some(SourceFilter::Apps), "code"));
+        assert_eq!(SourceFilter::parse("w:"), (Some(SourceFilter::Web), ""));
+        assert_eq!(SourceFilter::parse("x: code"), (None, "x: code"));
+        assert_eq!(SourceFilter::parse("code"), (None, "code"));
+        // Drive paths are navigated, not filtered
+        assert_eq!(SourceFilter::parse("F:\\Tools"), (None, "F:\\Tools"));
+        assert_eq!(SourceFilter::parse("f:/tools"), (None, "f:/tools"));
+    }
+
+    #[test]
+    fn filters_include_their_kind_of_result() {
+        let start_menu = &ProgramSource::StartMenu;
+        assert!(SourceFilter::Settings.includes(start_menu, Some("cpl")));
+        assert!(!SourceFilter::Apps.includes(start_menu, Some("msc")));
+        assert!(SourceFilter::Apps.includes(&ProgramSource::ProgramFiles, Some("exe")));
+        assert!(!SourceFilter::Apps.includes(&ProgramSource::Desktop, Some("lnk")));
+        assert!(SourceFilter::Files.includes(&ProgramSource::Desktop, Some("lnk")));
+        assert!(SourceFilter::Files.includes(&ProgramSource::Filesystem, None));
+        assert!(SourceFilter::Web.includes(&ProgramSource::Desktop, Some("url")));
+        assert!(SourceFilter::Web.includes(&ProgramSource::Provider, None));
+        assert!(!SourceFilter::Files.includes(&ProgramSource::Desktop, Some("url")));
+    }
+
+    /// Deterministic pseudo-random numbers (a linear congruential generator)
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) % n as u64) as usize
+        }
+
+        /// A string of 1 to `max_len` characters from `alphabet`
+        fn text(&mut self, alphabet: &[char], max_len: usize) -> String {
+            let len = 1 + self.below(max_len);
+            (0..len).map(|_| alphabet[self.below(alphabet.len())]).collect()
+        }
+    }
+
+    fn entry(display_name: &str) -> ProgramEntry {
+        ProgramEntry {
+            path: PathBuf::from(format!("C:/Apps/{}.exe", display_name)),
+            name: normalize(display_name),
+            display_name: display_name.to_string(),
+            base_name: None,
+            source: ProgramSource::ProgramFiles,
+            icon_path: None,
+            target: None,
+            target_name: None,
+            modified: None,
+            opener: None,
+        }
+    }
+
+    fn names(results: &[SearchResult]) -> Vec<String> {
+        results.iter().map(|r| r.entry.display_name.clone()).collect()
+    }
+
+    #[test]
+    fn pinned_entries_come_first() {
+        let entries = vec![entry("Code Editor"), entry("Codec Tool"), entry("Notepad")];
+        let mut engine = SearchEngine::new();
+        let usage = UsageMap::new();
+        engine.pinned = ["codec tool".to_string()].into();
+        let searched = engine.search("code", &entries, &usage, 10, || false).unwrap();
+        assert_eq!(names(&searched)[0], "Codec Tool");
+        let idle = engine.search("", &entries, &usage, 10, || false).unwrap();
+        assert_eq!(names(&idle)[0], "Codec Tool");
+    }
+
+    #[test]
+    fn streamed_best_matches_are_the_top_of_the_full_ranking() {
+        let alphabet: Vec<char> = "abcdeo ".chars().collect();
+        let mut rng = Lcg(7);
+        let entries: Vec<ProgramEntry> = (0..2000).map(|_| entry(&rng.text(&alphabet, 12))).collect();
+        let engine = SearchEngine::new();
+        let usage = UsageMap::new();
+
+        // Under the limit, so this holds every match, in batch order
+        let full = engine.search("bad", &entries, &usage, MAX_RESULTS_LIMIT, || false).unwrap();
+        assert!(full.len() > 20 && full.len() < MAX_RESULTS_LIMIT);
+        assert!(full.windows(2).all(|pair| {
+            let tie = engine.break_tie((&pair[0].entry, &pair[0].name_key), (&pair[1].entry, &pair[1].name_key), &usage);
+            pair[0].score > pair[1].score || (pair[0].score == pair[1].score && tie == Ordering::Less)
+        }));
+
+        let mut updates = Vec::new();
+        let best = engine
+            .search_streaming("bad", &entries, &usage, 20, || false, |best| updates.push(names(best)))
+            .unwrap();
+        assert_eq!(names(&best), names(&full[..20]));
+        assert_eq!(updates.last(), Some(&names(&best)));
+    }
+
+    #[test]
+    fn the_char_mask_never_rejects_a_fuzzy_match() {
+        let alphabet: Vec<char> = "abcxyz019 _-.éÄ".chars().collect();
+        let matcher = SkimMatcherV2::default().ignore_case();
+        let mut rng = Lcg(42);
+        let mut matched = 0;
+        for _ in 0..20_000 {
+            let text = normalize(&rng.text(&alphabet, 16));
+            let query = normalize(&rng.text(&alphabet, 4));
+            if matcher.fuzzy_match(&text, &query).is_some() {
+                matched += 1;
+                assert_eq!(char_mask(&query) & !char_mask(&text), 0, "{:?} matches {:?}", query, text);
+            }
+        }
+        // Enough matches for the property to mean something
+        assert!(matched > 1000);
+    }
+
+    #[test]
+    fn the_result_count_honors_max_results_within_its_bounds() {
+        let entries: Vec<ProgramEntry> = (0..MAX_RESULTS_LIMIT + 100).map(|i| entry(&format!("App {}", i))).collect();
+        let engine = SearchEngine::new();
+        let usage = UsageMap::new();
+        for query in ["app", ""] {
+            let count = |max_results| engine.search(query, &entries, &usage, max_results, || false).unwrap().len();
+            assert_eq!(count(7), 7);
+            assert_eq!(count(0), 1);
+            assert_eq!(count(MAX_RESULTS_LIMIT * 2), MAX_RESULTS_LIMIT);
+        }
+    }
+
+    #[test]
+    fn min_score_drops_weak_matches_and_keeps_strong_ones() {
+        let entries = vec![entry("Notepad"), entry("Network Options Test Editor")];
+        let mut engine = SearchEngine::new();
+        let usage = UsageMap::new();
+        let strong = engine.matcher.fuzzy_match("notepad", "note").unwrap();
+        let weak = engine.matcher.fuzzy_match("network options test editor", "note").unwrap();
+        assert!(weak < strong);
+
+        assert_eq!(names(&engine.search("note", &entries, &usage, 10, || false).unwrap()).len(), 2);
+        engine.min_score = weak + 1;
+        assert_eq!(names(&engine.search("note", &entries, &usage, 10, || false).unwrap()), ["Notepad"]);
+    }
+
+    #[test]
+    fn file_names_starting_with_the_query_are_boosted() {
+        // Only the file name of the first starts with the query, as wt.exe does for "Windows Terminal"
+        let mut editor = entry("Text Editor");
+        editor.path = PathBuf::from("C:/Apps/notepad.exe");
+        editor.name = "notepad".to_string();
+        let entries = vec![entry("Quick Notes"), editor];
+        let engine = SearchEngine::new();
+
+        let results = engine.search("note", &entries, &UsageMap::new(), 10, || false).unwrap();
+        assert_eq!(names(&results), ["Text Editor", "Quick Notes"]);
+        let base = engine.matcher.fuzzy_match("notepad", "note").unwrap();
+        assert_eq!(results[0].score, base + NAME_PREFIX_BOOST);
+    }
+
+    #[test]
+    fn an_exact_file_name_outranks_a_start_menu_prefix_match() {
+        let mut notepad = entry("Editor");
+        notepad.path = PathBuf::from("C:/Windows/notepad.exe");
+        notepad.name = "notepad".to_string();
+        let mut shortcut = entry("Notepad++ Portable");
+        shortcut.source = ProgramSource::StartMenu;
+        let entries = vec![shortcut, notepad];
+
+        let results = SearchEngine::new().search("notepad", &entries, &UsageMap::new(), 10, || false).unwrap();
+        assert_eq!(names(&results), ["Editor", "Notepad++ Portable"]);
+    }
+
+    #[test]
+    fn smart_case_matters_only_for_queries_with_uppercase() {
+        let entries = vec![entry("Visual Studio Code"), entry("barcode scanner")];
+        let usage = UsageMap::new();
+        let search = |case_sensitivity, query| {
+            let engine = SearchEngine::from_config(&Config { case_sensitivity, ..Config::default() });
+            let mut found = names(&engine.search(query, &entries, &usage, 10, || false).unwrap());
+            found.sort();
+            found
+        };
+
+        assert_eq!(search(CaseSensitivity::Smart, "code"), ["Visual Studio Code", "barcode scanner"]);
+        assert_eq!(search(CaseSensitivity::Smart, "Code"), ["Visual Studio Code"]);
+        assert_eq!(search(CaseSensitivity::Sensitive, "code"), ["barcode scanner"]);
+        assert_eq!(search(CaseSensitivity::Insensitive, "Code"), ["Visual Studio Code", "barcode scanner"]);
+    }
+
+    #[test]
+    fn each_tiebreaker_orders_equal_scores_its_way() {
+        use crate::indexer::UsageStat;
+        let entries = vec![entry("App Ccc"), entry("App Bb"), entry("App B"), entry("App Aaaa")];
+        // Both past the usage boost cap, so their scores stay equal
+        let launched = |launches| UsageStat { launches, last_launched: 0 };
+        let usage = UsageMap::from([
+            (entries[0].path.clone(), launched(20)),
+            (entries[1].path.clone(), launched(10)),
+        ]);
+        let search = |tiebreaker, usage: &UsageMap| {
+            let engine = SearchEngine::from_config(&Config { tiebreaker, ..Config::default() });
+            names(&engine.search("app", &entries, usage, 10, || false).unwrap())
+        };
+
+        assert_eq!(search(Tiebreaker::Usage, &usage), ["App Ccc", "App Bb", "App Aaaa", "App B"]);
+        let no_usage = UsageMap::new();
+        assert_eq!(search(Tiebreaker::Alphabetical, &no_usage), ["App Aaaa", "App B", "App Bb", "App Ccc"]);
+        assert_eq!(search(Tiebreaker::Shortest, &no_usage), ["App B", "App Bb", "App Ccc", "App Aaaa"]);
+    }
+
+    #[test]
+    fn grouped_results_keep_the_flat_order_per_source() {
+        let alphabet: Vec<char> = "abcdeo ".chars().collect();
+        let mut rng = Lcg(3);
+        let sources = [ProgramSource::ProgramFiles, ProgramSource::StartMenu, ProgramSource::Desktop];
+        let entries: Vec<ProgramEntry> = (0..300)
+            .map(|i| ProgramEntry { source: sources[i % 3].clone(), ..entry(&rng.text(&alphabet, 10)) })
             .collect();
+        let engine = SearchEngine::new();
+        let usage = UsageMap::new();
+
+        let flat = engine.search("bad", &entries, &usage, 50, || false).unwrap();
+        let grouped = engine.search_grouped("bad", &entries, &usage, 50, || false).unwrap();
+        assert_eq!(grouped.keys().collect::<Vec<_>>(), [&sources[1], &sources[2], &sources[0]]);
+        for (source, group) in &grouped {
+            let in_flat: Vec<SearchResult> = flat.iter().filter(|r| &r.entry.source == source).cloned().collect();
+            assert_eq!(names(group), names(&in_flat));
+        }
+        assert_eq!(grouped.values().map(Vec::len).sum::<usize>(), flat.len());
+    }
 
-        // Sort by score descending
-        results.sort_by(|a, b| b.score.cmp(&a.score));
+    #[test]
+    fn query_words_match_folder_initials() {
+        let path = Path::new("C:/Program Files/JetBrains/IntelliJ IDEA/bin/idea64.exe");
+        assert!(matches_path_components("jb ij", path));
+        assert!(matches_path_components("jet intel", path));
+        assert!(!matches_path_components("ij jb", path));
+        assert!(!matches_path_components("jb", path));
 
-        // Limit results
-        results.truncate(50);
+        let mut idea = entry("idea64");
+        idea.path = path.to_path_buf();
+        let entries = vec![idea, entry("Notepad")];
+        let usage = UsageMap::new();
+        let with = SearchEngine::from_config(&Config { path_component_matching: true, ..Config::default() });
+        assert_eq!(names(&with.search("jb ij", &entries, &usage, 10, || false).unwrap()), ["idea64"]);
+        assert!(SearchEngine::new().search("jb ij", &entries, &usage, 10, || false).unwrap().is_empty());
+    }
 
-        results
+    #[test]
+    fn a_cancelled_search_returns_nothing() {
+        let entries: Vec<ProgramEntry> = (0..CANCEL_CHECK_INTERVAL * 4).map(|i| entry(&format!("App {}", i))).collect();
+        let engine = SearchEngine::new();
+        let usage = UsageMap::new();
+        assert!(engine.search("app", &entries, &usage, 10, || true).is_none());
+
+        // Superseded while scoring: stops at the next check
+        let checks = std::cell::Cell::new(0);
+        let mut updates = 0;
+        let cancel_after_one_batch = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 1
+        };
+        let result = engine.search_streaming("app", &entries, &usage, 10, cancel_after_one_batch, |_| updates += 1);
+        assert!(result.is_none());
+        assert_eq!((checks.get(), updates), (2, 1));
+    }
+
+    #[test]
+    fn a_folder_query_lists_its_children_folders_first() {
+        let dir = std::env::temp_dir().join(format!("locksearch-list-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for folder in ["zeta", "Alpha"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+        }
+        for file in ["b.txt", "A.exe", "site.url"] {
+            fs::write(dir.join(file), b"").unwrap();
+        }
+
+        let listed = list_directory(&dir, 10);
+        let rows: Vec<(&str, ResultAction)> = listed.iter().map(|r| (r.entry.display_name.as_str(), r.action)).collect();
+        assert_eq!(
+            rows,
+            [
+                ("Alpha", ResultAction::Navigate),
+                ("zeta", ResultAction::Navigate),
+                ("A.exe", ResultAction::Launch),
+                ("b.txt", ResultAction::Launch),
+                ("site.url", ResultAction::OpenUri),
+            ]
+        );
+        assert!(listed.iter().all(|r| r.entry.source == ProgramSource::Filesystem));
+        assert_eq!(list_directory(&dir, 2).len(), 2);
+
+        // Only a query ending in a separator navigates, and only when enabled
+        let query = format!("{}/", dir.display());
+        let rank = |engine: &SearchEngine, query: &str| {
+            engine.rank(query, &[], &UsageMap::new(), 10, || false, |_| {}).unwrap()
+        };
+        let mut engine = SearchEngine::new();
+        engine.path_navigation = true;
+        assert_eq!(rank(&engine, &query).len(), 5);
+        assert!(rank(&engine, &dir.display().to_string()).is_empty());
+        engine.path_navigation = false;
+        assert!(rank(&engine, &query).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn the_idle_list_can_be_restricted_to_the_start_menu() {
+        let start_menu = |name: &str| ProgramEntry { source: ProgramSource::StartMenu, ..entry(name) };
+        // Program Files entries come first, so they would fill a plain cut
+        let entries = vec![
+            entry("helper"),
+            entry("updater"),
+            start_menu("Code"),
+            entry("tool"),
+            start_menu("Paint"),
+            start_menu("Word"),
+        ];
+
+        let config = Config { empty_query_sources: vec![ProgramSource::StartMenu], ..Config::default() };
+        let engine = SearchEngine::from_config(&config);
+        let idle = engine.search("", &entries, &UsageMap::new(), 2, || false).unwrap();
+        assert_eq!(names(&idle), ["Code", "Paint"]);
+
+        // By default every source is listed
+        let idle = SearchEngine::new().search("", &entries, &UsageMap::new(), 10, || false).unwrap();
+        assert_eq!(idle.len(), entries.len());
     }
 }
@@ -1,17 +1,40 @@
-use crate::config::Config;
-use crate::indexer::ProgramIndex;
-use crate::search::SearchEngine;
+use crate::config::{AfterLaunch, Config, Density, ReindexOnStartup, WindowChrome};
+use crate::i18n::Strings;
+use crate::indexer::{unix_now, IconReady, IndexError, IndexOptions, IndexProgress, IndexStats, ProgramIndex, ProgramSource, UsageStat};
+use crate::launcher::{self, ResultAction};
+use crate::platform;
+use crate::popup::Popup;
+use crate::providers::{self, ResultProvider};
+use crate::search::{self, normalize, RankedResult, SearchEngine, SearchResult, SourceFilter, MAX_RESULTS_LIMIT};
+use iced::event;
 use iced::keyboard;
-use iced::widget::{button, column, container, image, mouse_area, row, scrollable, svg, text, text_input, Column, Space};
+use iced::widget::{button, column, container, image, mouse_area, row, scrollable, svg, text, text_input, tooltip, Column, Space};
 use iced::{theme, window, Application, Color, Command, Element, Length, Subscription, Theme};
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use unicode_segmentation::UnicodeSegmentation;
 
 // Embedded SVG icons for window controls
 const ICON_MINIMIZE: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"12\" viewBox=\"0 0 12 12\"><line x1=\"2\" y1=\"6\" x2=\"10\" y2=\"6\" stroke=\"#7b8394\" stroke-width=\"1.5\" stroke-linecap=\"round\"/></svg>";
 const ICON_MAXIMIZE: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"12\" viewBox=\"0 0 12 12\"><rect x=\"2\" y=\"2\" width=\"8\" height=\"8\" rx=\"1\" fill=\"none\" stroke=\"#7b8394\" stroke-width=\"1.3\"/></svg>";
+const ICON_SEARCH: &str = "<svg xmlns=\"http://www.w3.org/2000/svg\" height=\"24px\" viewBox=\"0 0 24 24\" width=\"24px\" fill=\"none\" stroke=\"#8890a4\" stroke-width=\"2.5\" stroke-linecap=\"round\" stroke-linejoin=\"round\"><circle cx=\"11\" cy=\"11\" r=\"7\"/><line x1=\"16.5\" y1=\"16.5\" x2=\"21\" y2=\"21\"/></svg>";
+/// Pin, outlined: the title bar button while the window has the normal z-order
+const ICON_PIN: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"12\" viewBox=\"0 0 12 12\"><path d=\"M4.5 1.5h3l-.5 3 2 2h-6l2-2z\" fill=\"none\" stroke=\"#7b8394\" stroke-width=\"1.2\" stroke-linejoin=\"round\"/><line x1=\"6\" y1=\"6.5\" x2=\"6\" y2=\"10.5\" stroke=\"#7b8394\" stroke-width=\"1.2\" stroke-linecap=\"round\"/></svg>";
+/// Pin, filled: the title bar button while the window stays on top, and the
+/// mark of pinned results
+const ICON_PIN_FILLED: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"12\" viewBox=\"0 0 12 12\"><path d=\"M4.5 1.5h3l-.5 3 2 2h-6l2-2z\" fill=\"#c9cfdb\" stroke=\"#c9cfdb\" stroke-width=\"1.2\" stroke-linejoin=\"round\"/><line x1=\"6\" y1=\"6.5\" x2=\"6\" y2=\"10.5\" stroke=\"#c9cfdb\" stroke-width=\"1.2\" stroke-linecap=\"round\"/></svg>";
 const ICON_CLOSE: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"12\" height=\"12\" viewBox=\"0 0 12 12\"><line x1=\"3\" y1=\"3\" x2=\"9\" y2=\"9\" stroke=\"#7b8394\" stroke-width=\"1.5\" stroke-linecap=\"round\"/><line x1=\"9\" y1=\"3\" x2=\"3\" y2=\"9\" stroke=\"#7b8394\" stroke-width=\"1.5\" stroke-linecap=\"round\"/></svg>";
 
+// Embedded SVG icons shown when a program's own icon is unavailable
+const ICON_GENERIC_EXE: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"24\" viewBox=\"0 0 24 24\"><rect x=\"3\" y=\"4\" width=\"18\" height=\"16\" rx=\"2\" fill=\"none\" stroke=\"#aeb6c6\" stroke-width=\"1.6\"/><line x1=\"3\" y1=\"8.5\" x2=\"21\" y2=\"8.5\" stroke=\"#aeb6c6\" stroke-width=\"1.6\"/><circle cx=\"6\" cy=\"6.3\" r=\"0.8\" fill=\"#aeb6c6\"/><circle cx=\"8.6\" cy=\"6.3\" r=\"0.8\" fill=\"#aeb6c6\"/></svg>";
+const ICON_FOLDER: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"24\" viewBox=\"0 0 24 24\"><path d=\"M3 7a2 2 0 0 1 2-2h4l2 2h8a2 2 0 0 1 2 2v8a2 2 0 0 1-2 2H5a2 2 0 0 1-2-2z\" fill=\"none\" stroke=\"#aeb6c6\" stroke-width=\"1.6\" stroke-linejoin=\"round\"/></svg>";
+const ICON_SETTINGS: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"24\" viewBox=\"0 0 24 24\"><circle cx=\"12\" cy=\"12\" r=\"3\" fill=\"none\" stroke=\"#aeb6c6\" stroke-width=\"1.6\"/><path d=\"M12 3v3M12 18v3M3 12h3M18 12h3M5.6 5.6l2.1 2.1M16.3 16.3l2.1 2.1M5.6 18.4l2.1-2.1M16.3 7.7l2.1-2.1\" stroke=\"#aeb6c6\" stroke-width=\"1.6\" stroke-linecap=\"round\"/></svg>";
+const ICON_WEB_LINK: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"24\" viewBox=\"0 0 24 24\"><circle cx=\"12\" cy=\"12\" r=\"9\" fill=\"none\" stroke=\"#aeb6c6\" stroke-width=\"1.6\"/><ellipse cx=\"12\" cy=\"12\" rx=\"4\" ry=\"9\" fill=\"none\" stroke=\"#aeb6c6\" stroke-width=\"1.4\"/><line x1=\"3\" y1=\"12\" x2=\"21\" y2=\"12\" stroke=\"#aeb6c6\" stroke-width=\"1.4\"/></svg>";
+
 // =============== COLOR PALETTE ===============
 
 /// Outer window background — matches panel so no black gap
@@ -44,39 +67,300 @@ const TEXT_GRAY: Color = Color::from_rgb(0.48, 0.52, 0.60);
 /// Highlighted path text on selected items
 const TEXT_BLUE: Color = Color::from_rgb(0.32, 0.58, 0.84);
 
-/// Letter-placeholder icon background
-const ICON_BG: Color = Color::from_rgb(0.25, 0.28, 0.38);
+/// Launch error banner background — muted red
+const BG_ERROR: Color = Color::from_rgba(0.45, 0.12, 0.14, 0.85);
+
+/// Launch error banner text
+const TEXT_ERROR: Color = Color::from_rgb(1.0, 0.82, 0.82);
+
+/// Indexing problems indicator in the title bar — muted amber
+const TEXT_WARNING: Color = Color::from_rgb(0.90, 0.68, 0.32);
+
+/// Letter-placeholder icon backgrounds, picked per program name
+const ICON_BG_PALETTE: [Color; 8] = [
+    Color::from_rgb(0.25, 0.28, 0.38),
+    Color::from_rgb(0.36, 0.27, 0.52),
+    Color::from_rgb(0.20, 0.38, 0.50),
+    Color::from_rgb(0.18, 0.42, 0.36),
+    Color::from_rgb(0.48, 0.36, 0.20),
+    Color::from_rgb(0.50, 0.25, 0.30),
+    Color::from_rgb(0.26, 0.34, 0.56),
+    Color::from_rgb(0.40, 0.40, 0.24),
+];
+
+// =============== LAYOUT ===============
+
+/// Approximate height of the title bar, search bar and spacing above the results
+const CHROME_HEIGHT: f32 = 116.0;
+
+/// Approximate height of the custom title bar, absent with native chrome
+const TITLE_BAR_HEIGHT: f32 = 30.0;
+
+/// Largest icon in compact rows
+const COMPACT_ICON_SIZE: u16 = 24;
+
+/// Height of a row's name and path lines (15 and 11 px text at 1.3 line height)
+const ROW_TEXT_HEIGHT: u16 = 34;
+
+/// What a key binding does, see `KEY_BINDINGS`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    Next,
+    /// In an empty search box, recalls earlier searches instead
+    Previous,
+    First,
+    Last,
+    PageUp,
+    PageDown,
+    Activate,
+    RevealFolder,
+    RunElevated,
+    TogglePin,
+    ToggleStats,
+    RemoveEntry,
+    ToggleHelp,
+    Reindex,
+    ClearIconCache,
+    OpenConfigFolder,
+    OpenIconCacheFolder,
+    ToggleAlwaysOnTop,
+    /// Closes the open prompt, help or menu, or else clears the search
+    Escape,
+}
+
+/// A key, the modifiers that must be held with it, and what it does
+struct KeyBinding {
+    key: keyboard::Key<&'static str>,
+    ctrl: bool,
+    shift: bool,
+    action: KeyAction,
+}
+
+impl KeyBinding {
+    const fn new(key: keyboard::Key<&'static str>, action: KeyAction) -> Self {
+        Self { key, ctrl: false, shift: false, action }
+    }
+
+    const fn ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+
+    const fn shift(self) -> Self {
+        Self { shift: true, ..self }
+    }
+}
+
+/// Every key binding, in the order the F1 help lists them
+const KEY_BINDINGS: &[KeyBinding] = {
+    use keyboard::key::Named;
+    use keyboard::Key;
+    &[
+        KeyBinding::new(Key::Named(Named::ArrowDown), KeyAction::Next),
+        KeyBinding::new(Key::Named(Named::ArrowUp), KeyAction::Previous),
+        KeyBinding::new(Key::Named(Named::Home), KeyAction::First),
+        KeyBinding::new(Key::Named(Named::End), KeyAction::Last),
+        KeyBinding::new(Key::Named(Named::PageUp), KeyAction::PageUp),
+        KeyBinding::new(Key::Named(Named::PageDown), KeyAction::PageDown),
+        KeyBinding::new(Key::Named(Named::Enter), KeyAction::Activate),
+        KeyBinding::new(Key::Named(Named::Enter), KeyAction::RevealFolder).ctrl(),
+        KeyBinding::new(Key::Named(Named::Enter), KeyAction::RunElevated).ctrl().shift(),
+        KeyBinding::new(Key::Character("p"), KeyAction::TogglePin).ctrl(),
+        KeyBinding::new(Key::Named(Named::Escape), KeyAction::Escape),
+        KeyBinding::new(Key::Named(Named::Delete), KeyAction::RemoveEntry).shift(),
+        KeyBinding::new(Key::Character("i"), KeyAction::ToggleStats).ctrl(),
+        KeyBinding::new(Key::Named(Named::F1), KeyAction::ToggleHelp),
+        KeyBinding::new(Key::Named(Named::F5), KeyAction::Reindex),
+        KeyBinding::new(Key::Named(Named::F6), KeyAction::ClearIconCache),
+        KeyBinding::new(Key::Named(Named::F7), KeyAction::OpenConfigFolder),
+        KeyBinding::new(Key::Named(Named::F8), KeyAction::OpenIconCacheFolder),
+        KeyBinding::new(Key::Named(Named::F9), KeyAction::ToggleAlwaysOnTop),
+    ]
+};
+
+/// Query operators, listed in the F1 help after the mouse gestures
+const OPERATORS: &str = "a: s: f: w:";
+
+/// Sizes of a result row, set by the `density` option
+struct RowLayout {
+    icon_size: u16,
+    /// Vertical and horizontal padding inside the row
+    padding: [u16; 2],
+    /// Gap between the icon and the text
+    icon_gap: u16,
+    /// Gap between the name and the path
+    text_spacing: u16,
+    /// Gap between rows
+    row_spacing: u16,
+}
+
+/// How long a launch error stays visible
+const ERROR_DISPLAY_MS: u64 = 2500;
+
+/// Least time between updates of a running search's results; searches
+/// finishing sooner show only their final results
+const SEARCH_PROGRESS_MS: u64 = 60;
+
+/// Best results so far of a running search, with its id
+type SearchProgress = (u64, Vec<ProgramResult>);
 
 pub struct App {
     config: Config,
     program_index: Arc<ProgramIndex>,
     search_query: String,
+    /// Every result of the last search; only the first `shown_count()` are displayed
     search_results: Vec<ProgramResult>,
+    /// Id of the most recent search; older searches see it change and abort
+    query_id: Arc<AtomicU64>,
+    /// Id of the last search whose full results arrived
+    completed_query_id: u64,
+    /// Where running searches publish their best results so far
+    search_progress_tx: Arc<watch::Sender<SearchProgress>>,
+    /// Read by `subscription()`
+    search_progress_rx: watch::Receiver<SearchProgress>,
+    /// Pages of `page_size()` rows currently displayed ("Show more" adds one)
+    pages_shown: usize,
     selected_index: usize,
+    /// Identity of the selected row, used to re-find it when results change
+    selected_path: Option<PathBuf>,
     is_indexing: bool,
+    /// Entries in the index the last completed search ran over
     indexed_count: usize,
+    /// Search icon recolored to the theme accent, built once
+    search_icon: svg::Handle,
+    /// UI text in the configured language
+    strings: Strings,
+    /// Hint in the empty search box
+    search_placeholder: String,
+    /// Progress updates of the running index, consumed by `subscription()`
+    progress_rx: Option<watch::Receiver<IndexProgress>>,
+    /// Height of the content area, in the units layouts use
+    window_height: f32,
+    /// Factor iced applies on top of the display scale, see `ui_scale`
+    scale: f64,
+    launch_error: Option<String>,
+    /// Counts shown errors, so the timer of an earlier one can't clear a newer one
+    error_generation: u64,
+    /// Diagnostics panel contents, shown while `Some` (toggled with Ctrl+I)
+    index_stats: Option<IndexStats>,
+    /// Folders the last index run couldn't read, flagged in the title bar
+    index_errors: Vec<IndexError>,
+    /// Result row whose context menu is open
+    context_menu: Option<usize>,
+    /// Launch history of the context menu's entry, once loaded
+    context_usage: Option<UsageStat>,
+    /// `config.pinned` normalized, to mark pinned results as they arrive
+    pinned: HashSet<String>,
+    /// Currently held keyboard modifiers, used to vary the Enter action
+    modifiers: keyboard::Modifiers,
+    /// Set while a UAC prompt may hold focus, so losing focus doesn't hide the window
+    awaiting_elevation: bool,
+    /// Shortcut help shown in place of the results (toggled with F1)
+    show_help: bool,
+    /// User name being typed in the context menu for "Run as different user"
+    user_prompt: Option<String>,
+    /// Queries that led to a launch, most recent last
+    query_history: Vec<String>,
+    /// Position in `query_history` while recalling with Up, counted from the newest
+    history_cursor: Option<usize>,
+    /// Icons extracted since the entries of the current index were published,
+    /// by entry path; applied to results that were ranked without them
+    ready_icons: HashMap<PathBuf, PathBuf>,
+    /// Sources of results beyond the index, asked on every search
+    providers: Arc<Vec<Box<dyn ResultProvider>>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct ProgramResult {
     pub path: PathBuf,
     pub display_name: String,
+    /// Name before it was told apart from a namesake, see `ProgramEntry::base_name`
+    pub base_name: Option<String>,
     pub icon_path: Option<PathBuf>,
+    /// Whether `icon_path` points at a usable image, checked when results are produced
+    pub icon_ready: bool,
+    pub source: ProgramSource,
+    /// Extension of the program (or shortcut target), e.g. "exe"
+    pub extension: Option<String>,
+    /// Resolved shortcut target, if any
+    pub target: Option<PathBuf>,
+    /// Shown with a pin glyph; set when the results reach the UI
+    pub pinned: bool,
+    /// Program to open the file with instead of its association
+    pub opener: Option<PathBuf>,
+    /// What Enter does with this result
+    pub action: ResultAction,
+    /// Added within `highlight_new_days`
+    pub is_new: bool,
+    /// Characters of the display name matched by the query
+    pub match_indices: Vec<usize>,
+    /// The file was gone when the search ran (e.g. since uninstalled)
+    pub exists: bool,
+}
+
+impl ProgramResult {
+    /// Names pins and hides match, see `ProgramEntry::config_names`
+    pub fn config_names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.display_name.as_str()).chain(self.base_name.as_deref())
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum Message {
     SearchChanged(String),
-    SearchCompleted(Vec<ProgramResult>),
+    /// Results of the search with this id, the result last chosen for its
+    /// query, and how many entries the index held
+    SearchCompleted(u64, Vec<ProgramResult>, Option<PathBuf>, usize),
+    /// Best results so far of the search with this id
+    SearchProgress(u64, Vec<ProgramResult>),
     LaunchSelected,
-    KeyPressed(keyboard::Key),
-    IndexingProgress(bool, usize),
+    KeyPressed(keyboard::Key, keyboard::Modifiers),
+    IndexingStarted(Option<watch::Receiver<IndexProgress>>),
+    IndexingProgress(IndexProgress),
     StartIndexing,
-    CacheLoaded(bool),
+    /// Entries loaded from the cache, if there was one
+    CacheLoaded(Option<usize>),
     WindowMinimize,
     WindowMaximize,
     WindowClose,
     WindowDrag,
+    /// Flip `always_on_top` and save it
+    ToggleAlwaysOnTop,
+    WindowResized(f32),
+    /// Hide the error banner, if it still shows the error with this generation
+    ClearLaunchError(u64),
+    StatsLoaded(IndexStats),
+    ClearIconCache,
+    IconCacheCleared,
+    ModifiersChanged(keyboard::Modifiers),
+    ShowContextMenu(usize),
+    /// Left press on a result row; Shift runs it as administrator
+    ResultPressed(usize),
+    /// Middle press on a result row, opening its folder
+    ResultMiddlePressed(usize),
+    HideContextMenu,
+    LaunchElevated,
+    /// Ask for a user name to run the selected result as
+    PromptRunAsUser,
+    RunAsUserChanged(String),
+    LaunchAsUser,
+    OpenContainingFolder,
+    CopyPath,
+    TogglePin,
+    WindowFocused,
+    WindowUnfocused,
+    /// Another launch asked for the hidden window
+    ShowRequested,
+    ShowMore,
+    LaunchRecorded,
+    UsageLoaded(Option<UsageStat>),
+    IndexErrorsLoaded(Vec<IndexError>),
+    HideResult,
+    ConfigSaved(Result<(), String>),
+    HistoryLoaded(Vec<String>),
+    EntryRemoved(bool),
+    HideHelp,
+    /// An icon finished extracting: entry path, icon path
+    IconReady(PathBuf, PathBuf),
 }
 
 impl Application for App {
@@ -86,9 +370,18 @@ impl Application for App {
     type Flags = Config;
 
     fn new(config: Self::Flags) -> (Self, Command<Message>) {
-        let index = Arc::new(ProgramIndex::new());
+        let index = Arc::new(ProgramIndex::new(config.cache_dir()));
         let enable_cache = config.enable_cache;
         let cache_index = Arc::clone(&index);
+        let history_index = Arc::clone(&index);
+        let window_height = config.window_height;
+        let scale = ui_scale(&config);
+        let search_icon = search_icon_handle(&config.theme.accent);
+        let strings = Strings::load(config.language.as_deref());
+        let search_placeholder = search_placeholder(&config, &strings);
+        let pinned = normalized_names(&config.pinned);
+        let providers = Arc::new(providers::from_config(&config, &strings));
+        let (search_progress_tx, search_progress_rx) = watch::channel((0, Vec::new()));
 
         (
             Self {
@@ -96,19 +389,54 @@ impl Application for App {
                 program_index: index,
                 search_query: String::new(),
                 search_results: Vec::new(),
+                query_id: Arc::new(AtomicU64::new(0)),
+                completed_query_id: 0,
+                search_progress_tx: Arc::new(search_progress_tx),
+                search_progress_rx,
+                pages_shown: 1,
                 selected_index: 0,
+                selected_path: None,
                 is_indexing: false,
                 indexed_count: 0,
+                progress_rx: None,
+                search_icon,
+                strings,
+                search_placeholder,
+                window_height,
+                scale,
+                launch_error: None,
+                error_generation: 0,
+                index_stats: None,
+                index_errors: Vec::new(),
+                context_menu: None,
+                context_usage: None,
+                pinned,
+                modifiers: keyboard::Modifiers::default(),
+                awaiting_elevation: false,
+                show_help: false,
+                user_prompt: None,
+                query_history: Vec::new(),
+                history_cursor: None,
+                ready_icons: HashMap::new(),
+                providers,
             },
-            if enable_cache {
-                // Try loading cache first, then start indexing in background
+            Command::batch(vec![
+                // Focus the search box so users can type immediately
+                focus_search(),
                 Command::perform(
-                    async move { cache_index.load_cache().await },
-                    Message::CacheLoaded,
-                )
-            } else {
-                Command::perform(async {}, |_| Message::StartIndexing)
-            },
+                    async move { history_index.query_history().await },
+                    Message::HistoryLoaded,
+                ),
+                if enable_cache {
+                    // Try loading cache first, then start indexing in background
+                    Command::perform(
+                        async move { cache_index.load_cache().await },
+                        Message::CacheLoaded,
+                    )
+                } else {
+                    Command::perform(async {}, |_| Message::StartIndexing)
+                },
+            ]),
         )
     }
 
@@ -120,25 +448,206 @@ impl Application for App {
         match message {
             Message::SearchChanged(query) => {
                 self.search_query = query;
-                self.selected_index = 0;
+                self.history_cursor = None;
+                self.reset_selection();
                 return self.perform_search();
             }
-            Message::SearchCompleted(results) => {
+            Message::SearchCompleted(query_id, mut results, remembered, indexed_count) => {
+                if query_id != self.query_id.load(Ordering::SeqCst) {
+                    // Superseded (or cancelled) by a newer query
+                    return Command::none();
+                }
+                self.completed_query_id = query_id;
+                self.indexed_count = indexed_count;
+                self.apply_ready_icons(&mut results);
+                mark_pinned(&mut results, &self.pinned);
                 self.search_results = results;
-                if self.selected_index >= self.search_results.len() {
-                    self.selected_index = 0;
+                // Follow the previously selected entry to its new position, or
+                // preselect what was chosen the last time this query was typed
+                let followed = self.selected_path.as_deref().or(remembered.as_deref());
+                let index = followed_index(self.shown_results(), followed);
+                self.select(index);
+            }
+            Message::SearchProgress(query_id, mut results) => {
+                // Updates can arrive after the full results; those must win
+                if query_id == self.query_id.load(Ordering::SeqCst) && query_id > self.completed_query_id {
+                    self.apply_ready_icons(&mut results);
+                    mark_pinned(&mut results, &self.pinned);
+                    self.search_results = results;
+                    // The final results pick the selection; until then stay in range
+                    self.selected_index = self.selected_index.min(self.row_count().saturating_sub(1));
                 }
             }
+            Message::LaunchRecorded => {}
+            Message::ShowMore => {
+                return Command::batch(vec![self.show_more(), focus_search()]);
+            }
             Message::LaunchSelected => {
-                if let Some(result) = self.search_results.get(self.selected_index) {
-                    let _ = open::that(&result.path);
+                self.context_menu = None;
+                let command = self.activate_selected();
+                return Command::batch(vec![command, focus_search()]);
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+            }
+            Message::ShowContextMenu(index) => {
+                self.select(index);
+                self.context_menu = Some(index);
+                self.user_prompt = None;
+                self.context_usage = None;
+                if let Some(result) = self.shown_results().get(index) {
+                    let program_index = Arc::clone(&self.program_index);
+                    let path = result.path.clone();
+                    return Command::perform(
+                        async move { program_index.usage_for(&path).await },
+                        Message::UsageLoaded,
+                    );
+                }
+            }
+            Message::UsageLoaded(usage) => {
+                self.context_usage = usage;
+            }
+            Message::ResultPressed(index) => {
+                self.context_menu = None;
+                self.select(index);
+                // mouse_area doesn't report modifiers, so use the tracked ones
+                let command = if self.modifiers.shift() {
+                    self.launch_selected_elevated()
+                } else {
+                    Command::none()
+                };
+                return Command::batch(vec![command, focus_search()]);
+            }
+            Message::ResultMiddlePressed(index) => {
+                self.context_menu = None;
+                self.select(index);
+                let command = self.with_selected("open the folder of", platform::reveal_in_folder);
+                return Command::batch(vec![command, focus_search()]);
+            }
+            Message::IconReady(path, icon_path) => {
+                // Swap the letter placeholder of a visible row for the real icon
+                for result in self.search_results.iter_mut().filter(|r| r.path == path && !r.icon_ready) {
+                    result.icon_path = Some(icon_path.clone());
+                    result.icon_ready = true;
                 }
+                self.ready_icons.insert(path, icon_path);
+            }
+            Message::HideHelp => {
+                self.show_help = false;
+                return focus_search();
+            }
+            Message::HideContextMenu => {
+                self.context_menu = None;
+                self.user_prompt = None;
+                return focus_search();
+            }
+            Message::LaunchElevated => {
+                self.context_menu = None;
+                let command = self.launch_selected_elevated();
+                return Command::batch(vec![command, focus_search()]);
+            }
+            Message::OpenContainingFolder => {
+                self.context_menu = None;
+                let command = self.with_selected("open the folder of", platform::reveal_in_folder);
+                return Command::batch(vec![command, focus_search()]);
+            }
+            Message::PromptRunAsUser => {
+                self.user_prompt = Some(String::new());
+                return text_input::focus(user_input_id());
+            }
+            Message::RunAsUserChanged(user) => {
+                self.user_prompt = Some(user);
+            }
+            Message::LaunchAsUser => {
+                self.context_menu = None;
+                let user = self.user_prompt.take().unwrap_or_default().trim().to_string();
+                if user.is_empty() {
+                    return focus_search();
+                }
+                let command = self.launch_selected_as_user(&user);
+                return Command::batch(vec![command, focus_search()]);
+            }
+            Message::CopyPath => {
+                self.context_menu = None;
+                if let Some(result) = self.shown_results().get(self.selected_index) {
+                    let path = result.path.to_string_lossy().into_owned();
+                    return Command::batch(vec![iced::clipboard::write(path), focus_search()]);
+                }
+            }
+            Message::TogglePin => return self.toggle_pin(),
+            Message::HideResult => {
+                self.context_menu = None;
+                if let Some(result) = self.shown_results().get(self.selected_index) {
+                    let name = result.display_name.clone();
+                    self.config.hidden_programs.push(name.clone());
+                    let save = Command::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                Config::update_file(|config| {
+                                    if !config.hidden_programs.iter().any(|h| h.eq_ignore_ascii_case(&name)) {
+                                        config.hidden_programs.push(name);
+                                    }
+                                })
+                            })
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|saved| saved)
+                        },
+                        Message::ConfigSaved,
+                    );
+                    return Command::batch(vec![save, self.perform_search(), focus_search()]);
+                }
+            }
+            Message::EntryRemoved(removed) => {
+                if removed {
+                    return self.perform_search();
+                }
+            }
+            Message::HistoryLoaded(history) => {
+                self.query_history = history;
+            }
+            Message::ConfigSaved(result) => {
+                if let Err(e) = result {
+                    log::warn!("Failed to save config: {}", e);
+                    return self.show_error(format!("Failed to save config: {}", e));
+                }
+            }
+            Message::ClearLaunchError(generation) => {
+                if generation == self.error_generation {
+                    self.launch_error = None;
+                }
+            }
+            Message::StatsLoaded(stats) => {
+                self.index_stats = Some(stats);
+            }
+            Message::ClearIconCache => {
+                let index = Arc::clone(&self.program_index);
+                return Command::perform(
+                    async move {
+                        let _ = tokio::task::spawn_blocking(move || index.clear_icon_cache()).await;
+                    },
+                    |_| Message::IconCacheCleared,
+                );
+            }
+            Message::IconCacheCleared => {
+                // Reindex so icons are extracted again
+                return Command::perform(async {}, |_| Message::StartIndexing);
             }
             Message::CacheLoaded(loaded) => {
-                if loaded {
+                if loaded.is_some() {
                     // Cache loaded — show programs immediately
                     let search_cmd = self.perform_search();
-                    // Also start re-indexing in background
+                    // Also start re-indexing in background, unless configured otherwise
+                    let stale_after = Duration::from_secs(self.config.reindex_stale_hours.saturating_mul(60 * 60));
+                    let reindex = match self.config.reindex_on_startup {
+                        ReindexOnStartup::Always => true,
+                        ReindexOnStartup::Stale => self.program_index.cache_age().is_none_or(|age| age >= stale_after),
+                        ReindexOnStartup::Never => false,
+                    };
+                    if !reindex {
+                        log::info!("Using the cached index without rescanning");
+                        return search_cmd;
+                    }
                     let start_cmd = Command::perform(async {}, |_| Message::StartIndexing);
                     return Command::batch(vec![search_cmd, start_cmd]);
                 } else {
@@ -150,90 +659,205 @@ impl Application for App {
                 return window::minimize(window::Id::MAIN, true);
             }
             Message::WindowMaximize => {
-                return window::toggle_maximize(window::Id::MAIN);
+                return Command::batch(vec![window::toggle_maximize(window::Id::MAIN), focus_search()]);
             }
             Message::WindowClose => {
                 return window::close(window::Id::MAIN);
             }
             Message::WindowDrag => {
-                return window::drag(window::Id::MAIN);
+                return Command::batch(vec![window::drag(window::Id::MAIN), focus_search()]);
             }
-            Message::KeyPressed(key) => match key.as_ref() {
-                keyboard::Key::Named(keyboard::key::Named::ArrowDown) => {
-                    if !self.search_results.is_empty() {
-                        self.selected_index = (self.selected_index + 1) % self.search_results.len();
-                    }
+            Message::ToggleAlwaysOnTop => {
+                return Command::batch(vec![self.toggle_always_on_top(), focus_search()]);
+            }
+            Message::WindowResized(height) => {
+                let page_size = self.page_size();
+                // Window sizes don't include the app's own scale factor
+                self.window_height = height / self.scale as f32;
+                // A shorter window shows fewer rows; keep the selection on one of them
+                if self.selected_index >= self.row_count() {
+                    self.select(self.row_count().saturating_sub(1));
+                }
+                // A taller one shows more than the last search fetched
+                if self.page_size() > page_size {
+                    return self.perform_search();
+                }
+            }
+            Message::WindowFocused => {
+                self.awaiting_elevation = false;
+                return focus_search();
+            }
+            Message::ShowRequested => {
+                return Command::batch(vec![
+                    window::change_mode(window::Id::MAIN, window::Mode::Windowed),
+                    window::gain_focus(window::Id::MAIN),
+                    focus_search(),
+                ]);
+            }
+            Message::WindowUnfocused => {
+                let busy = self.context_menu.is_some() || self.awaiting_elevation;
+                if self.config.hide_on_focus_loss && !busy {
+                    return self.hide_window();
                 }
-                keyboard::Key::Named(keyboard::key::Named::ArrowUp) => {
-                    if !self.search_results.is_empty() {
-                        self.selected_index = if self.selected_index == 0 {
-                            self.search_results.len() - 1
+            }
+            Message::KeyPressed(key, modifiers) => {
+                let Some(action) = key_action(&key, modifiers) else {
+                    return Command::none();
+                };
+                let command = match action {
+                    KeyAction::TogglePin => self.toggle_pin(),
+                    KeyAction::ToggleStats => {
+                        if self.index_stats.take().is_some() {
+                            Command::none()
                         } else {
-                            self.selected_index - 1
-                        };
+                            let index = Arc::clone(&self.program_index);
+                            Command::perform(async move { index.stats().await }, Message::StatsLoaded)
+                        }
                     }
-                }
-                keyboard::Key::Named(keyboard::key::Named::Enter) => {
-                    if let Some(result) = self.search_results.get(self.selected_index) {
-                        let _ = open::that(&result.path);
+                    KeyAction::Next => {
+                        if self.row_count() > 0 {
+                            self.select((self.selected_index + 1) % self.row_count());
+                        }
+                        Command::none()
                     }
-                }
-                keyboard::Key::Named(keyboard::key::Named::Escape) => {
-                    self.search_query.clear();
-                    self.selected_index = 0;
-                    return self.perform_search();
-                }
-                _ => {}
-            },
+                    KeyAction::Previous if self.recalls_history() => self.recall_older_query(),
+                    KeyAction::Previous => {
+                        if self.row_count() > 0 {
+                            self.select(if self.selected_index == 0 {
+                                self.row_count() - 1
+                            } else {
+                                self.selected_index - 1
+                            });
+                        }
+                        Command::none()
+                    }
+                    KeyAction::First => {
+                        self.select(0);
+                        Command::none()
+                    }
+                    KeyAction::Last => {
+                        self.select(self.row_count().saturating_sub(1));
+                        Command::none()
+                    }
+                    KeyAction::PageUp => {
+                        self.select(self.selected_index.saturating_sub(self.visible_rows()));
+                        Command::none()
+                    }
+                    KeyAction::PageDown => {
+                        if self.row_count() > 0 {
+                            self.select((self.selected_index + self.visible_rows()).min(self.row_count() - 1));
+                        }
+                        Command::none()
+                    }
+                    KeyAction::RemoveEntry => {
+                        match self.shown_results().get(self.selected_index) {
+                            Some(result) => {
+                                let index = Arc::clone(&self.program_index);
+                                let path = result.path.clone();
+                                Command::perform(
+                                    async move { index.remove_entry(&path).await },
+                                    Message::EntryRemoved,
+                                )
+                            }
+                            None => Command::none(),
+                        }
+                    }
+                    KeyAction::ToggleHelp => {
+                        self.show_help = !self.show_help;
+                        Command::none()
+                    }
+                    KeyAction::Reindex => Command::perform(async {}, |_| Message::StartIndexing),
+                    KeyAction::ClearIconCache => Command::perform(async {}, |_| Message::ClearIconCache),
+                    KeyAction::OpenConfigFolder => self.open_folder(&Config::config_dir()),
+                    KeyAction::OpenIconCacheFolder => {
+                        let dir = self.program_index.icon_cache_dir().to_path_buf();
+                        self.open_folder(&dir)
+                    }
+                    KeyAction::ToggleAlwaysOnTop => self.toggle_always_on_top(),
+                    KeyAction::Activate => self.activate_selected(),
+                    KeyAction::RevealFolder => self.with_selected("open the folder of", platform::reveal_in_folder),
+                    KeyAction::RunElevated => self.launch_selected_elevated(),
+                    KeyAction::Escape if self.user_prompt.is_some() => {
+                        self.user_prompt = None;
+                        self.context_menu = None;
+                        Command::none()
+                    }
+                    KeyAction::Escape if self.show_help => {
+                        self.show_help = false;
+                        Command::none()
+                    }
+                    KeyAction::Escape if self.context_menu.is_some() => {
+                        self.context_menu = None;
+                        Command::none()
+                    }
+                    KeyAction::Escape => {
+                        self.search_query.clear();
+                        self.history_cursor = None;
+                        self.reset_selection();
+                        self.perform_search()
+                    }
+                };
+                // Keep typing routed to the search box after any handled key
+                return Command::batch(vec![command, focus_search()]);
+            }
             Message::StartIndexing => {
                 if !self.is_indexing {
                     self.is_indexing = true;
                     let index = Arc::clone(&self.program_index);
+                    let options = IndexOptions::from_config(&self.config);
                     return Command::perform(
-                        async move {
-                            // start_indexing spawns a blocking task and returns immediately
-                            index.start_indexing().await;
-                            // Signal that indexing has started — we'll poll for completion
-                            (true, 0usize)
-                        },
-                        |(is_idx, count)| Message::IndexingProgress(is_idx, count),
+                        // start_indexing spawns a blocking task and returns immediately
+                        async move { index.start_indexing(options).await },
+                        Message::IndexingStarted,
                     );
                 }
             }
-            Message::IndexingProgress(is_indexing, count) => {
-                self.is_indexing = is_indexing;
-                self.indexed_count = count;
-                if is_indexing {
-                    // Poll every 100ms until indexing completes
-                    let index = Arc::clone(&self.program_index);
-                    return Command::perform(
-                        async move {
-                            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                            (index.is_indexing().await, index.indexed_count().await)
-                        },
-                        |(is_idx, count)| Message::IndexingProgress(is_idx, count),
-                    );
-                } else {
+            Message::IndexingStarted(rx) => {
+                // None means another run was already in progress
+                self.is_indexing = rx.is_some();
+                if rx.is_some() {
+                    self.ready_icons.clear();
+                }
+                self.progress_rx = rx;
+            }
+            Message::IndexingProgress(progress) => {
+                if progress.done && self.progress_rx.take().is_some() {
                     // Indexing finished — refresh search results
+                    self.is_indexing = false;
+                    let index = Arc::clone(&self.program_index);
+                    return Command::batch(vec![
+                        self.perform_search(),
+                        Command::perform(async move { index.errors().await }, Message::IndexErrorsLoaded),
+                    ]);
+                } else if progress.partial {
+                    // A cold start shows what was found so far
                     return self.perform_search();
                 }
             }
+            Message::IndexErrorsLoaded(errors) => {
+                self.index_errors = errors;
+            }
         }
         Command::none()
     }
 
     fn view(&self) -> Element<'_, Message> {
-        // Search icon — bold magnifying glass
-        let search_icon_svg: &[u8] = b"<svg xmlns=\"http://www.w3.org/2000/svg\" height=\"24px\" viewBox=\"0 0 24 24\" width=\"24px\" fill=\"none\" stroke=\"#8890a4\" stroke-width=\"2.5\" stroke-linecap=\"round\" stroke-linejoin=\"round\"><circle cx=\"11\" cy=\"11\" r=\"7\"/><line x1=\"16.5\" y1=\"16.5\" x2=\"21\" y2=\"21\"/></svg>";
-        let search_icon = container(
-            svg(svg::Handle::from_memory(search_icon_svg))
-                .width(self.config.search_icon_size)
-                .height(self.config.search_icon_size),
-        )
-        .padding([0, 4, 0, 0]);
+        // Search icon — bold magnifying glass in the theme accent
+        let search_icon: Element<Message> = if self.config.show_search_icon {
+            container(
+                svg(self.search_icon.clone())
+                    .width(self.config.search_icon_size)
+                    .height(self.config.search_icon_size),
+            )
+            .padding([0, 4, 0, 0])
+            .into()
+        } else {
+            Space::with_width(0).into()
+        };
 
         // Search input
-        let search_input = text_input("Search apps, files, and settings...", &self.search_query)
+        let search_input = text_input(&self.search_placeholder, &self.search_query)
+            .id(search_input_id())
             .on_input(Message::SearchChanged)
             .on_submit(Message::LaunchSelected)
             .padding([14, 8])
@@ -248,28 +872,85 @@ impl Application for App {
         let search_bar = container(search_row)
             .width(Length::Fill)
             .style(theme::Container::Custom(Box::new(SearchBarStyle)));
+        // The text box captures its own clicks, so only the icon and padding drag
+        let search_bar: Element<Message> = if self.config.draggable_search_bar {
+            mouse_area(search_bar).on_press(Message::WindowDrag).into()
+        } else {
+            search_bar.into()
+        };
+
+        // Launch error banner
+        let error_banner: Element<Message> = match &self.launch_error {
+            Some(error) => container(text(error).size(12).style(theme::Text::Color(TEXT_ERROR)))
+                .width(Length::Fill)
+                .padding([8, 14])
+                .style(theme::Container::Custom(Box::new(ErrorBannerStyle)))
+                .into(),
+            None => Space::with_height(0).into(),
+        };
+
+        // Index diagnostics panel
+        let stats_panel: Element<Message> = match &self.index_stats {
+            Some(stats) => stats_view(stats),
+            None => Space::with_height(0).into(),
+        };
 
         // Results area
-        let results_content: Element<Message> = if self.search_results.is_empty() {
-            if !self.search_query.is_empty() {
-                container(text("No results").size(13).style(theme::Text::Color(TEXT_GRAY)))
+        let results_content: Element<Message> = if self.show_help {
+            self.help_view()
+        } else if self.search_results.is_empty() {
+            // An index still being built isn't the same as a query matching nothing
+            let notice = if self.is_indexing && self.indexed_count == 0 {
+                Some(&self.strings.indexing_programs)
+            } else if !self.search_query.is_empty() {
+                Some(&self.strings.no_results)
+            } else {
+                None
+            };
+            match notice {
+                Some(notice) => container(text(notice).size(13).style(theme::Text::Color(TEXT_GRAY)))
                     .width(Length::Fill)
                     .padding([40, 0])
                     .center_x()
-                    .into()
-            } else {
-                Space::with_height(0).into()
+                    .into(),
+                None => Space::with_height(0).into(),
             }
         } else {
-            let mut col: Column<Message> = column![].spacing(2);
-            for (idx, result) in self.search_results.iter().enumerate() {
+            let mut col: Column<Message> = column![].spacing(self.row_layout().row_spacing);
+            let mut current_source: Option<&ProgramSource> = None;
+            for (idx, result) in self.shown_results().iter().enumerate() {
+                if self.config.group_by_source && current_source != Some(&result.source) {
+                    current_source = Some(&result.source);
+                    col = col.push(section_header(self.strings.source_label(&result.source)));
+                }
                 let is_selected = idx == self.selected_index;
-                col = col.push(self.result_row(result, is_selected));
+                let row = mouse_area(self.result_row(result, is_selected))
+                    .on_press(Message::ResultPressed(idx))
+                    .on_middle_press(Message::ResultMiddlePressed(idx))
+                    .on_right_press(Message::ShowContextMenu(idx));
+                col = if self.context_menu == Some(idx) {
+                    col.push(Popup::new(row, self.context_menu_view(result), Message::HideContextMenu))
+                } else {
+                    col.push(row)
+                };
+            }
+            let hidden = self.search_results.len() - self.shown_count();
+            if hidden > 0 {
+                let is_selected = self.selected_index == self.shown_count();
+                col = col.push(self.show_more_row(hidden.min(self.page_size()), is_selected));
             }
             scrollable(col).height(Length::Fill).width(Length::Fill).into()
         };
 
         // Window control buttons
+        let on_top_icon = if self.config.always_on_top { ICON_PIN_FILLED } else { ICON_PIN };
+        let btn_on_top = button(
+            svg(svg::Handle::from_memory(on_top_icon)).width(14).height(14)
+        )
+            .on_press(Message::ToggleAlwaysOnTop)
+            .padding([6, 10])
+            .style(theme::Button::Custom(Box::new(TitleBarButtonStyle)));
+
         let btn_minimize = button(
             svg(svg::Handle::from_memory(ICON_MINIMIZE)).width(14).height(14)
         )
@@ -291,26 +972,53 @@ impl Application for App {
             .padding([6, 10])
             .style(theme::Button::Custom(Box::new(CloseButtonStyle)));
 
-        // Draggable title bar
-        let title_label = mouse_area(
-            container(
-                text("LockSearch")
-                    .size(12)
-                    .style(theme::Text::Color(TEXT_GRAY))
-            )
-            .width(Length::Fill)
-            .padding([8, 8])
+        let title_label = container(
+            text("LockSearch")
+                .size(12)
+                .style(theme::Text::Color(TEXT_GRAY))
         )
-        .on_press(Message::WindowDrag);
+        .width(Length::Fill)
+        .padding([8, 8]);
 
-        let title_bar = row![
-            title_label,
-            btn_minimize,
-            btn_maximize,
-            btn_close,
-        ]
-        .align_items(iced::Alignment::Center)
-        .padding([0, 4, 0, 4]);
+        let index_warning: Element<Message> = if self.index_errors.is_empty() {
+            Space::with_width(0).into()
+        } else {
+            let details = self
+                .index_errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n");
+            let label = text(self.strings.folders_not_indexed(self.index_errors.len()))
+                .size(11)
+                .style(theme::Text::Color(TEXT_WARNING));
+            tooltip(container(label).padding([6, 8]), text(details).size(11), tooltip::Position::Bottom)
+                .style(theme::Container::Box)
+                .into()
+        };
+
+        // The system draws the caption with native chrome; only the warning and pin remain.
+        // Frameless, the whole bar drags: buttons capture their own presses.
+        let title_bar: Element<Message> = match self.config.window_chrome {
+            WindowChrome::Frameless => mouse_area(
+                row![
+                    title_label,
+                    index_warning,
+                    btn_on_top,
+                    btn_minimize,
+                    btn_maximize,
+                    btn_close,
+                ]
+                .align_items(iced::Alignment::Center)
+                .padding([0, 4, 0, 4]),
+            )
+            .on_press(Message::WindowDrag)
+            .into(),
+            WindowChrome::Native => row![Space::with_width(Length::Fill), index_warning, btn_on_top]
+                .align_items(iced::Alignment::Center)
+                .padding([0, 4, 0, 4])
+                .into(),
+        };
 
         // Main panel
         let panel = container(
@@ -318,7 +1026,10 @@ impl Application for App {
                 title_bar,
                 Space::with_height(4),
                 search_bar,
-                Space::with_height(12),
+                Space::with_height(6),
+                error_banner,
+                stats_panel,
+                Space::with_height(6),
                 results_content,
                 Space::with_height(8),
             ]
@@ -341,22 +1052,598 @@ impl Application for App {
         Theme::Dark
     }
 
+    fn scale_factor(&self) -> f64 {
+        self.scale
+    }
+
     fn subscription(&self) -> Subscription<Message> {
-        keyboard::on_key_press(|key, _modifiers| Some(Message::KeyPressed(key)))
+        let mut subscriptions = vec![
+            event::listen_with(handle_event),
+            icon_updates(Arc::clone(&self.program_index)),
+            show_requests(),
+        ];
+        subscriptions.push(search_progress(self.search_progress_rx.clone()));
+        if let Some(rx) = &self.progress_rx {
+            subscriptions.push(indexing_progress(rx.clone()));
+        }
+        Subscription::batch(subscriptions)
+    }
+}
+
+/// Window level for the `always_on_top` setting; `Normal` restores the usual z-order
+pub fn window_level(always_on_top: bool) -> window::Level {
+    if always_on_top {
+        window::Level::AlwaysOnTop
+    } else {
+        window::Level::Normal
+    }
+}
+
+/// The search icon with its stroke set to `accent`, if that is a valid hex color
+fn search_icon_handle(accent: &str) -> svg::Handle {
+    let accent = accent.trim();
+    let is_hex_color = accent.len() == 7
+        && accent.starts_with('#')
+        && accent[1..].chars().all(|c| c.is_ascii_hexdigit());
+    let svg_text = if is_hex_color {
+        ICON_SEARCH.replace("#8890a4", accent)
+    } else {
+        log::warn!("Ignoring invalid accent color {:?}", accent);
+        ICON_SEARCH.to_string()
+    };
+    svg::Handle::from_memory(svg_text.into_bytes())
+}
+
+/// The configured search box hint, or the default one
+fn search_placeholder(config: &Config, strings: &Strings) -> String {
+    match config.search_placeholder.as_deref().filter(|p| !p.trim().is_empty()) {
+        Some(placeholder) => placeholder.to_string(),
+        None => strings.search_placeholder.clone(),
+    }
+}
+
+/// Generic icon for a result without a usable icon of its own, chosen by
+/// extension and source. `None` leaves it to the letter tile.
+fn category_icon(result: &ProgramResult) -> Option<&'static [u8]> {
+    if result.action == ResultAction::Navigate {
+        return Some(ICON_FOLDER);
+    }
+    match result.extension.as_deref() {
+        Some("url" | "website") => Some(ICON_WEB_LINK),
+        Some("msc" | "cpl") => Some(ICON_SETTINGS),
+        // A shortcut target without an extension is usually a folder
+        None => Some(ICON_FOLDER),
+        Some("exe" | "com" | "bat" | "cmd" | "ps1" | "msi") => Some(ICON_GENERIC_EXE),
+        Some(_) => match result.source {
+            ProgramSource::ProgramFiles => Some(ICON_GENERIC_EXE),
+            ProgramSource::StartMenu
+            | ProgramSource::Desktop
+            | ProgramSource::Filesystem
+            | ProgramSource::Provider => None,
+        },
+    }
+}
+
+/// First visible grapheme of a name, uppercased, for letter placeholders.
+/// Works on grapheme clusters so CJK characters and emoji stay intact.
+fn placeholder_glyph(name: &str) -> String {
+    name.graphemes(true)
+        .find(|g| g.chars().any(|c| !c.is_whitespace() && !c.is_control()))
+        .map(str::to_uppercase)
+        .unwrap_or_else(|| "?".to_string())
+}
+
+/// Placeholder background picked from a stable (FNV-1a) hash of the name,
+/// so the same program always gets the same tile color
+fn placeholder_color(name: &str) -> Color {
+    let hash = name
+        .bytes()
+        .fold(0x811c_9dc5_u32, |h, b| (h ^ b as u32).wrapping_mul(0x0100_0193));
+    ICON_BG_PALETTE[hash as usize % ICON_BG_PALETTE.len()]
+}
+
+/// Scale factor of the UI on top of the display's: 1 to follow the DPI
+/// setting, or its inverse so sizes land on raw pixels with `respect_dpi` off
+pub fn ui_scale(config: &Config) -> f64 {
+    if config.respect_dpi {
+        1.0
+    } else {
+        1.0 / platform::system_scale_factor()
+    }
+}
+
+/// Stable identifier of the search box, used to (re)focus it
+fn search_input_id() -> text_input::Id {
+    text_input::Id::new("search-input")
+}
+
+/// Stable identifier of the user name box of "Run as different user"
+fn user_input_id() -> text_input::Id {
+    text_input::Id::new("user-input")
+}
+
+/// Command that moves keyboard focus back to the search box
+fn focus_search() -> Command<Message> {
+    text_input::focus(search_input_id())
+}
+
+/// Turn index progress updates into messages. Ends with a final `done`
+/// update once the indexer drops its sender.
+fn indexing_progress(rx: watch::Receiver<IndexProgress>) -> Subscription<Message> {
+    iced::subscription::unfold("indexing-progress", Some(rx), |state| async move {
+        match state {
+            Some(mut rx) => {
+                if rx.changed().await.is_ok() {
+                    let progress = *rx.borrow_and_update();
+                    (Message::IndexingProgress(progress), Some(rx))
+                } else {
+                    let progress = IndexProgress { done: true, ..*rx.borrow() };
+                    (Message::IndexingProgress(progress), None)
+                }
+            }
+            None => iced::futures::future::pending().await,
+        }
+    })
+}
+
+/// Turn the best results so far of running searches into `SearchProgress` messages
+fn search_progress(rx: watch::Receiver<SearchProgress>) -> Subscription<Message> {
+    iced::subscription::unfold("search-progress", rx, |mut rx| async move {
+        if rx.changed().await.is_err() {
+            // The app holds the sender, so this only happens on shutdown
+            return iced::futures::future::pending().await;
+        }
+        let (query_id, results) = rx.borrow_and_update().clone();
+        (Message::SearchProgress(query_id, results), rx)
+    })
+}
+
+/// State of the `icon_updates` subscription
+enum IconListener {
+    Starting(Arc<ProgramIndex>),
+    Listening(broadcast::Receiver<IconReady>),
+}
+
+/// `ShowRequested` whenever a later launch asks for the hidden window
+fn show_requests() -> Subscription<Message> {
+    iced::subscription::unfold("show-requests", (), |()| async {
+        match tokio::task::spawn_blocking(platform::wait_for_show_request).await {
+            Ok(true) => (Message::ShowRequested, ()),
+            _ => iced::futures::future::pending().await,
+        }
+    })
+}
+
+/// Turn icons extracted in the background into `IconReady` messages
+fn icon_updates(index: Arc<ProgramIndex>) -> Subscription<Message> {
+    iced::subscription::unfold("icon-updates", IconListener::Starting(index), |state| async move {
+        let mut rx = match state {
+            IconListener::Starting(index) => index.icon_updates(),
+            IconListener::Listening(rx) => rx,
+        };
+        loop {
+            match rx.recv().await {
+                Ok(icon) => return (Message::IconReady(icon.path, icon.icon_path), IconListener::Listening(rx)),
+                // Skipped icons still appear once extraction finishes and the next search runs
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::debug!("Missed {} icon updates", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return iced::futures::future::pending().await,
+            }
+        }
+    })
+}
+
+/// Map raw runtime events to app messages.
+///
+/// Key presses captured by the search input are dropped, except for the list
+/// navigation keys (Home/End move the text cursor otherwise) and Escape (which
+/// would only unfocus the input). Ctrl+letter shortcuts are always forwarded.
+fn handle_event(event: iced::Event, status: event::Status) -> Option<Message> {
+    match event {
+        iced::Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
+            let always_handled = matches!(
+                key.as_ref(),
+                keyboard::Key::Named(
+                    keyboard::key::Named::Home
+                        | keyboard::key::Named::End
+                        | keyboard::key::Named::PageUp
+                        | keyboard::key::Named::PageDown
+                        | keyboard::key::Named::Escape
+                )
+            );
+            let is_shortcut = modifiers.control() && matches!(key, keyboard::Key::Character(_));
+            // The search box would otherwise take Shift+Delete as a forward delete
+            let removes_entry = modifiers.shift() && key == keyboard::Key::Named(keyboard::key::Named::Delete);
+            if status == event::Status::Ignored || always_handled || is_shortcut || removes_entry {
+                Some(Message::KeyPressed(key, modifiers))
+            } else {
+                None
+            }
+        }
+        iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+            Some(Message::ModifiersChanged(modifiers))
+        }
+        iced::Event::Window(_, window::Event::Resized { height, .. }) => {
+            Some(Message::WindowResized(height as f32))
+        }
+        iced::Event::Window(_, window::Event::Focused) => Some(Message::WindowFocused),
+        iced::Event::Window(_, window::Event::Unfocused) => Some(Message::WindowUnfocused),
+        _ => None,
     }
 }
 
+/// What `key` does with `modifiers` held: of the bindings whose modifiers
+/// are all held, the one needing the most
+fn key_action(key: &keyboard::Key, modifiers: keyboard::Modifiers) -> Option<KeyAction> {
+    KEY_BINDINGS
+        .iter()
+        .filter(|binding| binding.key == key.as_ref())
+        .filter(|binding| (!binding.ctrl || modifiers.control()) && (!binding.shift || modifiers.shift()))
+        .max_by_key(|binding| binding.ctrl as u8 + binding.shift as u8)
+        .map(|binding| binding.action)
+}
+
+/// How the F1 help writes a binding, e.g. "Ctrl+Shift+Enter"
+fn key_label(binding: &KeyBinding, strings: &Strings) -> String {
+    use keyboard::key::Named;
+    let key = match binding.key {
+        keyboard::Key::Named(Named::ArrowDown) => "↓".to_string(),
+        keyboard::Key::Named(Named::ArrowUp) => "↑".to_string(),
+        keyboard::Key::Named(Named::Home) => strings.key_home.clone(),
+        keyboard::Key::Named(Named::End) => strings.key_end.clone(),
+        keyboard::Key::Named(Named::PageUp) => strings.key_page_up.clone(),
+        keyboard::Key::Named(Named::PageDown) => strings.key_page_down.clone(),
+        keyboard::Key::Named(Named::Enter) => strings.key_enter.clone(),
+        keyboard::Key::Named(Named::Escape) => strings.key_escape.clone(),
+        keyboard::Key::Named(Named::Delete) => strings.key_delete.clone(),
+        // Function keys read the same everywhere
+        keyboard::Key::Named(named) => format!("{:?}", named),
+        keyboard::Key::Character(c) => c.to_uppercase(),
+        keyboard::Key::Unidentified => String::new(),
+    };
+    let ctrl = binding.ctrl.then_some(strings.key_ctrl.as_str());
+    let shift = binding.shift.then_some(strings.key_shift.as_str());
+    ctrl.into_iter().chain(shift).chain([key.as_str()]).collect::<Vec<_>>().join("+")
+}
+
 impl App {
-    fn result_row(&self, result: &ProgramResult, is_selected: bool) -> Element<'_, Message> {
-        let icon_size = self.config.program_icon_size;
+    /// Select the row at `index`, remembering its identity across result refreshes
+    fn select(&mut self, index: usize) {
+        self.selected_index = index;
+        self.selected_path = self.shown_results().get(index).map(|r| r.path.clone());
+    }
+
+    /// Drop the current selection and extra pages so the next results start at the top
+    fn reset_selection(&mut self) {
+        self.selected_index = 0;
+        self.selected_path = None;
+        self.pages_shown = 1;
+    }
+
+    /// Results per page: as many rows as fit the window, at most `max_results`
+    fn page_size(&self) -> usize {
+        self.visible_rows().min(self.config.max_results.max(1))
+    }
+
+    /// Number of results currently displayed
+    fn shown_count(&self) -> usize {
+        (self.page_size() * self.pages_shown).min(self.search_results.len())
+    }
+
+    /// Fill in icons extracted after `results` were ranked
+    fn apply_ready_icons(&self, results: &mut [ProgramResult]) {
+        for result in results.iter_mut().filter(|r| !r.icon_ready) {
+            if let Some(icon_path) = self.ready_icons.get(&result.path) {
+                result.icon_path = Some(icon_path.clone());
+                result.icon_ready = true;
+            }
+        }
+    }
 
-        let icon_element: Element<Message> = if let Some(ref icon_path) = result.icon_path {
-            let use_real_icon = icon_path.exists()
-                && std::fs::metadata(icon_path)
-                    .map(|m| m.len() > 500)
-                    .unwrap_or(false);
+    fn shown_results(&self) -> &[ProgramResult] {
+        &self.search_results[..self.shown_count()]
+    }
 
-            if use_real_icon {
+    /// Selectable rows: the displayed results plus the "Show more" row, if any
+    fn row_count(&self) -> usize {
+        let shown = self.shown_count();
+        if shown < self.search_results.len() {
+            shown + 1
+        } else {
+            shown
+        }
+    }
+
+    /// Number of result rows that fit in the current window height
+    fn visible_rows(&self) -> usize {
+        let layout = self.row_layout();
+        let content_height = layout.icon_size.max(ROW_TEXT_HEIGHT + layout.text_spacing);
+        let row_height = (content_height + 2 * layout.padding[0] + layout.row_spacing) as f32;
+        let chrome_height = match self.config.window_chrome {
+            WindowChrome::Frameless => CHROME_HEIGHT,
+            WindowChrome::Native => CHROME_HEIGHT - TITLE_BAR_HEIGHT,
+        };
+        (((self.window_height - chrome_height) / row_height).floor() as usize).max(1)
+    }
+
+    /// Act on the selected result: launch it, or reveal the next page on "Show more"
+    fn activate_selected(&mut self) -> Command<Message> {
+        if self.selected_index == self.shown_count() && self.row_count() > self.shown_count() {
+            // The "Show more" row is selected
+            self.show_more()
+        } else {
+            self.launch_selected()
+        }
+    }
+
+    /// Show the next page from the results already held. A search fetches
+    /// one page past the rows shown, so the page after the new one is only
+    /// searched for when the held results fill the new one exactly.
+    fn show_more(&mut self) -> Command<Message> {
+        self.pages_shown += 1;
+        let shown = self.page_size() * self.pages_shown;
+        // Provided rows move along to stay last on screen, see `providers::merge`
+        let (provided, programs): (Vec<_>, Vec<_>) = std::mem::take(&mut self.search_results)
+            .into_iter()
+            .partition(|r| r.source == ProgramSource::Provider);
+        let held = programs.len();
+        self.search_results = providers::merge(programs, provided, shown);
+        if held >= shown && held < shown + self.page_size() && held < MAX_RESULTS_LIMIT {
+            self.perform_search()
+        } else {
+            Command::none()
+        }
+    }
+
+    /// Launch the selected result, surfacing any failure in the error banner
+    fn launch_selected(&mut self) -> Command<Message> {
+        let Some(result) = self.shown_results().get(self.selected_index) else {
+            return Command::none();
+        };
+        let action = result.action;
+        if action == ResultAction::Navigate {
+            // Continue navigating from the chosen folder
+            let mut query = result.path.to_string_lossy().into_owned();
+            query.push(std::path::MAIN_SEPARATOR);
+            self.search_query = query;
+            self.history_cursor = None;
+            self.reset_selection();
+            return Command::batch(vec![self.perform_search(), text_input::move_cursor_to_end(search_input_id())]);
+        }
+        let target = result.target.clone();
+        let opener = result.opener.clone();
+        let method = self.config.launch_method;
+        let verb = match action {
+            ResultAction::Launch => "launch",
+            ResultAction::OpenUri => "open the link of",
+            ResultAction::RevealFolder | ResultAction::Navigate => "open the folder",
+        };
+        let command = self.with_selected(verb, |path| {
+            launcher::perform(action, path, target.as_deref(), opener.as_deref(), method)
+        });
+        self.finish_launch(command)
+    }
+
+    fn launch_selected_elevated(&mut self) -> Command<Message> {
+        // The UAC prompt takes focus; don't treat that as the user clicking away
+        self.awaiting_elevation = true;
+        let command = self.with_selected("launch as administrator", platform::launch_elevated);
+        self.finish_launch(command)
+    }
+
+    /// Run the selected result (a shortcut's target) as `user`
+    fn launch_selected_as_user(&mut self, user: &str) -> Command<Message> {
+        // runas asks for the password in a console that takes focus
+        self.awaiting_elevation = true;
+        let target = self.shown_results().get(self.selected_index).and_then(|r| r.target.clone());
+        let command = self.with_selected("launch as a different user", |path| {
+            platform::launch_as_user(target.as_deref().unwrap_or(path), user)
+        });
+        self.finish_launch(command)
+    }
+
+    /// Follow up a launch attempt: record it and apply `after_launch`, unless it failed
+    fn finish_launch(&mut self, command: Command<Message>) -> Command<Message> {
+        if self.launch_error.is_some() {
+            return command;
+        }
+        let record = self.record_launch();
+        self.history_cursor = None;
+        let after = match self.config.after_launch {
+            AfterLaunch::Hide => self.hide_window(),
+            AfterLaunch::Clear => {
+                self.search_query.clear();
+                self.reset_selection();
+                self.perform_search()
+            }
+            AfterLaunch::Keep => Command::none(),
+        };
+        Command::batch(vec![command, record, after])
+    }
+
+    /// Count a launch of the selected result and add its query to the history
+    fn record_launch(&mut self) -> Command<Message> {
+        let Some(result) = self.shown_results().get(self.selected_index) else {
+            return Command::none();
+        };
+        let index = Arc::clone(&self.program_index);
+        let path = result.path.clone();
+        // A web search URL only ever comes back for the same query
+        let counted = result.source != ProgramSource::Provider;
+        let typed = self.search_query.trim().to_string();
+        let query = normalize(&typed);
+        if !typed.is_empty() {
+            self.query_history.retain(|q| *q != typed);
+            self.query_history.push(typed.clone());
+        }
+        Command::perform(
+            async move {
+                if counted {
+                    index.record_launch(&path).await;
+                }
+                if !query.is_empty() {
+                    if counted {
+                        index.remember_selection(&query, &path).await;
+                    }
+                    index.remember_query(&typed).await;
+                }
+            },
+            |_| Message::LaunchRecorded,
+        )
+    }
+
+    /// Whether Up recalls an earlier query instead of moving the selection:
+    /// in an empty box, or while recalling with the first row selected
+    fn recalls_history(&self) -> bool {
+        let recalling = self.history_cursor.is_some() && self.selected_index == 0;
+        !self.query_history.is_empty() && (self.search_query.is_empty() || recalling)
+    }
+
+    /// Replace the query with the next older one from the history, stopping at the oldest
+    fn recall_older_query(&mut self) -> Command<Message> {
+        let cursor = self
+            .history_cursor
+            .map_or(0, |c| (c + 1).min(self.query_history.len() - 1));
+        self.history_cursor = Some(cursor);
+        self.search_query = self.query_history[self.query_history.len() - 1 - cursor].clone();
+        self.reset_selection();
+        Command::batch(vec![self.perform_search(), text_input::move_cursor_to_end(search_input_id())])
+    }
+
+    /// Get the launcher out of the way and reset it for next time. Launching
+    /// LockSearch again brings the hidden window back (see `show_requests`).
+    fn hide_window(&mut self) -> Command<Message> {
+        self.search_query.clear();
+        self.history_cursor = None;
+        self.reset_selection();
+        Command::batch(vec![
+            self.perform_search(),
+            window::change_mode(window::Id::MAIN, window::Mode::Hidden),
+        ])
+    }
+
+    /// Run an action on the selected result's path, reporting failures in the banner
+    fn with_selected(
+        &mut self,
+        verb: &str,
+        action: impl FnOnce(&Path) -> std::io::Result<()>,
+    ) -> Command<Message> {
+        let Some(result) = self.shown_results().get(self.selected_index) else {
+            return Command::none();
+        };
+
+        match action(&result.path) {
+            Ok(()) => {
+                log::info!("Ran '{}' on {}", verb, result.path.display());
+                self.launch_error = None;
+                Command::none()
+            }
+            Err(e) => {
+                log::warn!("Failed to {} {}: {}", verb, result.path.display(), e);
+                let message = format!("Failed to {} {}: {}", verb, result.display_name, e);
+                let feedback = self.config.launch_feedback;
+                if feedback.flashes() {
+                    platform::flash_window();
+                }
+                if feedback.beeps() {
+                    platform::error_beep();
+                }
+                self.show_error(message)
+            }
+        }
+    }
+
+    /// Pin the selected result, or unpin it, and save the change
+    fn toggle_pin(&mut self) -> Command<Message> {
+        self.context_menu = None;
+        let Some(result) = self.shown_results().get(self.selected_index) else {
+            return Command::none();
+        };
+        // Unpinning also drops a pin made before the name was disambiguated
+        let names: Vec<String> = result.config_names().map(str::to_string).collect();
+        let pin = !result.pinned;
+        set_pinned(&mut self.config.pinned, &names, pin);
+        self.pinned = normalized_names(&self.config.pinned);
+        mark_pinned(&mut self.search_results, &self.pinned);
+        let save = Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    Config::update_file(|config| set_pinned(&mut config.pinned, &names, pin))
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|saved| saved)
+            },
+            Message::ConfigSaved,
+        );
+        // Pinned results rank higher, so the order changes too
+        Command::batch(vec![save, self.perform_search(), focus_search()])
+    }
+
+    /// Move the window to the other z-order level and save the choice
+    fn toggle_always_on_top(&mut self) -> Command<Message> {
+        let on_top = !self.config.always_on_top;
+        self.config.always_on_top = on_top;
+        let save = Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || Config::update_file(|config| config.always_on_top = on_top))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|saved| saved)
+            },
+            Message::ConfigSaved,
+        );
+        Command::batch(vec![window::change_level(window::Id::MAIN, window_level(on_top)), save])
+    }
+
+    /// Open a folder in the system file manager
+    fn open_folder(&mut self, dir: &Path) -> Command<Message> {
+        match open::that(dir) {
+            Ok(()) => Command::none(),
+            Err(e) => {
+                log::warn!("Failed to open {}: {}", dir.display(), e);
+                self.show_error(format!("Failed to open {}: {}", dir.display(), e))
+            }
+        }
+    }
+
+    /// Show a transient message in the error banner
+    fn show_error(&mut self, message: String) -> Command<Message> {
+        self.launch_error = Some(message);
+        self.error_generation += 1;
+        let generation = self.error_generation;
+        Command::perform(
+            tokio::time::sleep(tokio::time::Duration::from_millis(ERROR_DISPLAY_MS)),
+            move |_| Message::ClearLaunchError(generation),
+        )
+    }
+
+    fn row_layout(&self) -> RowLayout {
+        match self.config.density {
+            Density::Comfortable => RowLayout {
+                icon_size: self.config.program_icon_size,
+                padding: [10, 14],
+                icon_gap: 16,
+                text_spacing: 3,
+                row_spacing: 2,
+            },
+            Density::Compact => RowLayout {
+                icon_size: self.config.program_icon_size.min(COMPACT_ICON_SIZE),
+                padding: [4, 10],
+                icon_gap: 10,
+                text_spacing: 1,
+                row_spacing: 0,
+            },
+        }
+    }
+
+    fn result_row(&self, result: &ProgramResult, is_selected: bool) -> Element<'_, Message> {
+        let layout = self.row_layout();
+        let icon_size = layout.icon_size;
+
+        let icon_element: Element<Message> = match &result.icon_path {
+            Some(icon_path) if result.icon_ready => {
                 let handle = image::Handle::from_path(icon_path);
                 container(
                     image(handle)
@@ -365,47 +1652,220 @@ impl App {
                 )
                 .style(theme::Container::Custom(Box::new(IconContainerStyle)))
                 .into()
-            } else {
-                self.letter_placeholder(&result.display_name)
             }
-        } else {
-            self.letter_placeholder(&result.display_name)
+            _ => match category_icon(result) {
+                Some(icon) => self.category_placeholder(icon),
+                None => self.letter_placeholder(&result.display_name),
+            },
         };
 
-        let name = text(&result.display_name)
-            .size(15)
-            .style(theme::Text::Color(TEXT_WHITE));
+        let name = highlighted_name(&result.display_name, &result.match_indices);
+        let mut name_row = row![name].spacing(6).align_items(iced::Alignment::Center);
+        if let Some(ext) = result.extension.as_ref().filter(|_| self.config.show_extension) {
+            name_row = name_row.push(text(format!(".{}", ext)).size(12).style(theme::Text::Color(TEXT_GRAY)));
+        }
+        if result.pinned {
+            name_row = name_row.push(svg(svg::Handle::from_memory(ICON_PIN_FILLED)).width(11).height(11));
+        }
+        if result.is_new {
+            name_row = name_row.push(
+                container(text(&self.strings.new_badge).size(10).style(theme::Text::Color(TEXT_WHITE)))
+                    .padding([1, 6])
+                    .style(theme::Container::Custom(Box::new(NewBadgeStyle))),
+            );
+        }
 
-        let path_str = result.path.to_string_lossy();
-        let path_color = if is_selected { TEXT_BLUE } else { TEXT_GRAY };
-        let path = text(path_str.to_string())
-            .size(11)
-            .style(theme::Text::Color(path_color));
+        let path = self.path_breadcrumb(result, is_selected);
 
-        let text_col = column![name, path].spacing(3);
+        let text_col = column![name_row, path].spacing(layout.text_spacing);
 
         let content_row = row![icon_element, text_col]
-            .spacing(16)
+            .spacing(layout.icon_gap)
             .align_items(iced::Alignment::Center)
-            .padding([10, 14]);
+            .padding(layout.padding);
 
-        container(content_row)
+        let row = container(content_row)
             .width(Length::Fill)
-            .style(theme::Container::Custom(Box::new(ResultItemStyle { is_selected })))
+            .style(theme::Container::Custom(Box::new(ResultItemStyle { is_selected })));
+
+        // Full details for telling apart entries whose path is cut off
+        let mut details = format!(
+            "{}\n{}: {}",
+            path_label(&result.path),
+            self.strings.source,
+            self.strings.source_label(&result.source)
+        );
+        if let Some(target) = &result.target {
+            details.push_str(&format!("\n{}: {}", self.strings.target, path_label(target)));
+        }
+        tooltip(row, text(details).size(11), tooltip::Position::FollowCursor)
+            .padding(6)
+            .style(theme::Container::Box)
             .into()
     }
 
-    fn letter_placeholder(&self, name: &str) -> Element<'_, Message> {
-        let icon_size = self.config.program_icon_size;
+    /// The end of a result's path, with folders the query matched (when
+    /// matching folder names) in the match color
+    fn path_breadcrumb(&self, result: &ProgramResult, is_selected: bool) -> Element<'_, Message> {
+        let base_color = match (result.exists, is_selected) {
+            (false, _) => TEXT_WARNING,
+            (true, true) => TEXT_BLUE,
+            (true, false) => TEXT_GRAY,
+        };
+        let label = path_label(&result.path);
+        let (prefix, components) = breadcrumb(&label, self.config.path_breadcrumb_depth);
+
+        let highlights = if self.config.path_component_matching {
+            let (_, query) = SourceFilter::parse(&self.search_query);
+            breadcrumb_highlights(&normalize(query), &result.path, components.len())
+        } else {
+            Vec::new()
+        };
+
+        let separator = std::path::MAIN_SEPARATOR.to_string();
+        let mut segments = row![text(prefix).size(11).style(theme::Text::Color(base_color))];
+        for (i, component) in components.iter().enumerate() {
+            if i > 0 {
+                segments = segments.push(text(&separator).size(11).style(theme::Text::Color(base_color)));
+            }
+            let matched = highlights.get(i).copied().unwrap_or(false);
+            let color = match (matched && result.exists, is_selected) {
+                (false, _) => base_color,
+                (true, true) => TEXT_WHITE,
+                (true, false) => TEXT_BLUE,
+            };
+            segments = segments.push(text(component).size(11).style(theme::Text::Color(color)));
+        }
+        if !result.exists {
+            segments = segments.push(
+                text(format!(" {}", self.strings.not_found)).size(11).style(theme::Text::Color(TEXT_WARNING)),
+            );
+        }
+        segments.into()
+    }
+
+    /// Actions for a result, opened over the list right below its row
+    fn context_menu_view(&self, result: &ProgramResult) -> Element<'_, Message> {
+        let strings = &self.strings;
+        if let Some(user) = &self.user_prompt {
+            let input = text_input(&strings.user_name_hint, user)
+                .id(user_input_id())
+                .on_input(Message::RunAsUserChanged)
+                .on_submit(Message::LaunchAsUser)
+                .padding([6, 12])
+                .size(13);
+            let title = text(&strings.run_as_user).size(11).style(theme::Text::Color(TEXT_GRAY));
+            return container(column![title, input].spacing(4))
+                .width(240)
+                .padding(8)
+                .style(theme::Container::Custom(Box::new(ContextMenuStyle)))
+                .into();
+        }
+
+        let pin_label = if result.pinned { &strings.unpin } else { &strings.pin };
+        let items = column![
+            menu_item(&strings.launch, Message::LaunchSelected),
+            menu_item(&strings.run_as_admin, Message::LaunchElevated),
+            menu_item(&strings.run_as_user, Message::PromptRunAsUser),
+            menu_item(&strings.open_folder, Message::OpenContainingFolder),
+            menu_item(&strings.copy_path, Message::CopyPath),
+            menu_item(pin_label, Message::TogglePin),
+            menu_item(&strings.hide_result, Message::HideResult),
+        ]
+        .spacing(2);
+        let items = match &self.context_usage {
+            Some(usage) if usage.launches > 0 => {
+                let label = text(strings.launched(usage.launches as usize))
+                    .size(11)
+                    .style(theme::Text::Color(TEXT_GRAY));
+                items.push(container(label).padding([4, 12]))
+            }
+            _ => items,
+        };
+
+        container(items)
+            .width(240)
+            .padding(4)
+            .style(theme::Container::Custom(Box::new(ContextMenuStyle)))
+            .into()
+    }
+
+    /// Card listing `KEY_BINDINGS`, mouse gestures and query operators;
+    /// clicking anywhere on it closes it
+    fn help_view(&self) -> Element<'_, Message> {
+        let strings = &self.strings;
+        let keys = KEY_BINDINGS
+            .iter()
+            .map(|binding| (key_label(binding, strings), strings.key_action(binding.action)));
+        let others = [
+            (&strings.gesture_click, &strings.help_click),
+            (&strings.gesture_shift_click, &strings.help_shift_click),
+            (&strings.gesture_middle_click, &strings.help_middle_click),
+            (&strings.gesture_right_click, &strings.help_right_click),
+        ]
+        .into_iter()
+        .map(|(gesture, help)| (gesture.clone(), help.as_str()))
+        .chain([(OPERATORS.to_string(), strings.help_operators.as_str())]);
+        let rows = keys.chain(others).fold(column![].spacing(6), |rows, (keys, action)| {
+            rows.push(
+                row![
+                    text(keys).size(12).width(140).style(theme::Text::Color(TEXT_BLUE)),
+                    text(action).size(12).style(theme::Text::Color(TEXT_WHITE)),
+                ]
+                .spacing(12),
+            )
+        });
+        let card = container(
+            column![
+                text(&strings.keyboard_shortcuts).size(14).style(theme::Text::Color(TEXT_WHITE)),
+                rows,
+            ]
+            .spacing(12),
+        )
+        .width(Length::Fill)
+        .padding(16)
+        .style(theme::Container::Custom(Box::new(ContextMenuStyle)));
+
+        mouse_area(scrollable(container(card).padding([8, 0])).height(Length::Fill))
+            .on_press(Message::HideHelp)
+            .into()
+    }
+
+    /// Row at the end of a truncated list that reveals the next page
+    fn show_more_row(&self, count: usize, is_selected: bool) -> Element<'_, Message> {
+        let label = text(self.strings.show_more(count))
+            .size(13)
+            .style(theme::Text::Color(if is_selected { TEXT_BLUE } else { TEXT_GRAY }));
+
+        mouse_area(
+            container(label)
+                .width(Length::Fill)
+                .padding([8, 14])
+                .center_x()
+                .style(theme::Container::Custom(Box::new(ResultItemStyle { is_selected }))),
+        )
+        .on_press(Message::ShowMore)
+        .into()
+    }
 
-        let first_char = name
-            .chars()
-            .find(|c| c.is_alphanumeric())
-            .unwrap_or('?')
-            .to_uppercase()
-            .to_string();
+    /// Neutral tile with a generic icon for the program's type
+    fn category_placeholder(&self, icon: &'static [u8]) -> Element<'_, Message> {
+        let icon_size = self.row_layout().icon_size;
+        let glyph_size = (icon_size as f32 * 0.6) as u16;
 
-        let letter = text(first_char)
+        container(svg(svg::Handle::from_memory(icon)).width(glyph_size).height(glyph_size))
+            .width(icon_size)
+            .height(icon_size)
+            .center_x()
+            .center_y()
+            .style(theme::Container::Custom(Box::new(LetterPlaceholderStyle(ICON_BG_PALETTE[0]))))
+            .into()
+    }
+
+    fn letter_placeholder(&self, name: &str) -> Element<'_, Message> {
+        let icon_size = self.row_layout().icon_size;
+
+        let letter = text(placeholder_glyph(name))
             .size((icon_size as f32 * 0.5) as u16)
             .style(theme::Text::Color(TEXT_WHITE));
 
@@ -414,36 +1874,227 @@ impl App {
             .height(icon_size)
             .center_x()
             .center_y()
-            .style(theme::Container::Custom(Box::new(LetterPlaceholderStyle)))
+            .style(theme::Container::Custom(Box::new(LetterPlaceholderStyle(placeholder_color(name)))))
             .into()
     }
 
     fn perform_search(&self) -> Command<Message> {
         let query = self.search_query.clone();
         let index = Arc::clone(&self.program_index);
-        let max_results = self.config.max_results;
+        let engine = SearchEngine::from_config(&self.config);
+        let new_since = new_since(self.config.highlight_new_days);
+        let latest_id = Arc::clone(&self.query_id);
+        let query_id = latest_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let providers = Arc::clone(&self.providers);
+        let progress_tx = Arc::clone(&self.search_progress_tx);
+        // Updates only carry the rows on screen
+        let shown = self.page_size() * self.pages_shown;
+        // One page past them, so "Show more" can tell how many follow
+        let max_results = (shown + self.page_size()).min(MAX_RESULTS_LIMIT);
 
         Command::perform(
             async move {
                 let entries = index.get_entries().await;
-                let engine = SearchEngine::new();
-                let results = engine.search(&query, &entries);
-
-                results
-                    .into_iter()
-                    .take(max_results)
-                    .map(|r| ProgramResult {
-                        path: r.entry.path,
-                        display_name: r.entry.display_name,
-                        icon_path: r.entry.icon_path,
-                    })
-                    .collect()
+                let indexed_count = entries.len();
+                let usage = index.usage().await;
+                let remembered = index.remembered_selection(&normalize(query.trim())).await;
+                let results = tokio::task::spawn_blocking(move || {
+                    let is_cancelled = || latest_id.load(Ordering::SeqCst) != query_id;
+                    let interval = Duration::from_millis(SEARCH_PROGRESS_MS);
+                    let mut last_update = Instant::now();
+                    // A long search (a big index) shows its best matches so far
+                    let on_update = |best: &[SearchResult]| {
+                        if last_update.elapsed() < interval {
+                            return;
+                        }
+                        last_update = Instant::now();
+                        let preview = engine.ranked(&query, best[..best.len().min(shown)].to_vec());
+                        progress_tx.send_replace((query_id, program_results(preview, new_since)));
+                    };
+                    let results = engine.rank(&query, &entries, &usage, max_results, is_cancelled, on_update)?;
+                    let results = program_results(results, new_since);
+                    Some(providers::merge(results, providers::query_all(&providers, &query), shown))
+                })
+                .await;
+                // A cancelled search reports nothing; its stale id gets it ignored
+                (query_id, results.ok().flatten().unwrap_or_default(), remembered, indexed_count)
+            },
+            |(query_id, results, remembered, indexed_count)| {
+                Message::SearchCompleted(query_id, results, remembered, indexed_count)
             },
-            Message::SearchCompleted,
         )
     }
 }
 
+/// Ranked results in the form the view shows. Icons and files are checked
+/// here, so the view never touches the filesystem, also for the page past
+/// the rows shown that "Show more" reveals without searching again.
+fn program_results(results: Vec<RankedResult>, new_since: Option<u64>) -> Vec<ProgramResult> {
+    results
+        .into_iter()
+        .map(|r| ProgramResult {
+            icon_ready: r.entry.icon_path.as_deref().is_some_and(is_usable_icon),
+            exists: r.entry.path.exists(),
+            extension: r.entry.extension(),
+            action: r.action,
+            match_indices: r.match_indices,
+            is_new: new_since
+                .zip(r.entry.modified)
+                .is_some_and(|(since, modified)| modified >= since),
+            target: r.entry.target,
+            pinned: false,
+            opener: r.entry.opener,
+            path: r.entry.path,
+            display_name: r.entry.display_name,
+            base_name: r.entry.base_name,
+            icon_path: r.entry.icon_path,
+            source: r.entry.source,
+        })
+        .collect()
+}
+
+/// Earliest time (Unix seconds) counting as new under `highlight_new_days`,
+/// or `None` when nothing is highlighted
+fn new_since(days: u64) -> Option<u64> {
+    (days > 0).then(|| unix_now().saturating_sub(days.saturating_mul(24 * 60 * 60)))
+}
+
+/// Row of `results` showing `path`, or the top row when it is gone (or none is followed)
+fn followed_index(results: &[ProgramResult], path: Option<&Path>) -> usize {
+    path.and_then(|path| results.iter().position(|r| r.path == path)).unwrap_or(0)
+}
+
+/// Remove `names` from `pinned`, then add the first of them back if `pin`
+fn set_pinned(pinned: &mut Vec<String>, names: &[String], pin: bool) {
+    pinned.retain(|p| !names.iter().any(|name| p.eq_ignore_ascii_case(name)));
+    if let Some(name) = names.first().filter(|_| pin) {
+        pinned.push(name.clone());
+    }
+}
+
+fn normalized_names(names: &[String]) -> HashSet<String> {
+    names.iter().map(|name| normalize(name)).collect()
+}
+
+/// Flag the results named in `pinned` (normalized, see `normalized_names`)
+fn mark_pinned(results: &mut [ProgramResult], pinned: &HashSet<String>) {
+    for result in results {
+        let is_pinned = result.config_names().any(|name| pinned.contains(&normalize(name)));
+        result.pinned = is_pinned;
+    }
+}
+
+/// A result name with the characters at `indices` (from the search's
+/// lowercased copy of `name`) drawn in the match color
+fn highlighted_name<'a>(name: &str, indices: &[usize]) -> Element<'a, Message> {
+    let chars: Vec<char> = name.chars().collect();
+    // Normalizing can change the length of a few names; then positions don't line up
+    if indices.is_empty() || normalize(name).chars().count() != chars.len() {
+        return text(name).size(15).style(theme::Text::Color(TEXT_WHITE)).into();
+    }
+
+    let mut segments = row![];
+    let mut start = 0;
+    while start < chars.len() {
+        let matched = indices.contains(&start);
+        let end = (start..chars.len())
+            .find(|i| indices.contains(i) != matched)
+            .unwrap_or(chars.len());
+        let color = if matched { TEXT_BLUE } else { TEXT_WHITE };
+        let segment: String = chars[start..end].iter().collect();
+        segments = segments.push(text(segment).size(15).style(theme::Text::Color(color)));
+        start = end;
+    }
+    segments.into()
+}
+
+/// Printable form of a path, with undecodable segments shown as `_` instead
+/// of raw replacement glyphs and without the long-path `\\?\` prefix
+fn path_label(path: &Path) -> String {
+    let label = path.to_string_lossy().replace(char::REPLACEMENT_CHARACTER, "_");
+    match label.strip_prefix(r"\\?\") {
+        Some(plain) => plain.to_string(),
+        None => label,
+    }
+}
+
+/// Split a path label into what goes before its components and the last
+/// `keep` components (all of them for 0): `("…\\", ["Vendor", "App", "app.exe"])`.
+/// An untruncated label keeps its leading separators, e.g. of a UNC path.
+fn breadcrumb(label: &str, keep: usize) -> (String, Vec<&str>) {
+    let components: Vec<&str> = label.split(['\\', '/']).filter(|c| !c.is_empty()).collect();
+    if keep == 0 || components.len() <= keep {
+        let leading = label.len() - label.trim_start_matches(['\\', '/']).len();
+        return (label[..leading].to_string(), components);
+    }
+    let prefix = format!("…{}", std::path::MAIN_SEPARATOR);
+    (prefix, components[components.len() - keep..].to_vec())
+}
+
+/// Which of the last `shown` names of `path` the search's folder matching
+/// (see `search::path_component_matches`) matched the query's words with
+fn breadcrumb_highlights(query_lower: &str, path: &Path, shown: usize) -> Vec<bool> {
+    let matches = search::path_component_matches(query_lower, path).unwrap_or_default();
+    // The breadcrumb ends with the path's names, though it may also show a drive
+    let names = search::path_names(path).count();
+    (0..shown)
+        .map(|i| (names + i).checked_sub(shown).is_some_and(|name| matches.contains(&name)))
+        .collect()
+}
+
+/// Extracted icons smaller than this are blank placeholders from the shell
+fn is_usable_icon(icon_path: &Path) -> bool {
+    std::fs::metadata(icon_path)
+        .map(|m| m.len() > 500)
+        .unwrap_or(false)
+}
+
+/// Summary of the last indexing run
+fn stats_view(stats: &IndexStats) -> Element<'_, Message> {
+    let per_source = stats
+        .per_source
+        .iter()
+        .map(|(source, count)| format!("{}: {}", source.label(), count))
+        .collect::<Vec<_>>()
+        .join("  ·  ");
+    let duration = stats
+        .last_duration
+        .map(|d| format!("{:.2}s", d.as_secs_f32()))
+        .unwrap_or_else(|| "not run yet".to_string());
+
+    let lines = column![
+        text(per_source).size(12).style(theme::Text::Color(TEXT_WHITE)),
+        text(format!(
+            "{} files walked  ·  {} merged  ·  {} links skipped  ·  last index {}",
+            stats.files_walked, stats.merged, stats.reparse_points, duration
+        ))
+        .size(11)
+        .style(theme::Text::Color(TEXT_GRAY)),
+    ]
+    .spacing(4);
+
+    container(lines)
+        .width(Length::Fill)
+        .padding([8, 14])
+        .into()
+}
+
+fn menu_item(label: &str, message: Message) -> Element<'static, Message> {
+    button(text(label).size(13))
+        .on_press(message)
+        .width(Length::Fill)
+        .padding([6, 12])
+        .style(theme::Button::Custom(Box::new(MenuItemStyle)))
+        .into()
+}
+
+/// Small muted heading above a group of results
+fn section_header(label: &str) -> Element<'_, Message> {
+    container(text(label).size(11).style(theme::Text::Color(TEXT_GRAY)))
+        .padding([8, 14, 2, 14])
+        .into()
+}
+
 // =============== STYLES ===============
 
 struct OuterStyle;
@@ -529,12 +2180,44 @@ impl container::StyleSheet for ResultItemStyle {
     }
 }
 
-struct LetterPlaceholderStyle;
+struct ErrorBannerStyle;
+impl container::StyleSheet for ErrorBannerStyle {
+    type Style = Theme;
+    fn appearance(&self, _: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(iced::Background::Color(BG_ERROR)),
+            border: iced::Border {
+                color: Color::TRANSPARENT,
+                width: 0.0,
+                radius: 8.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+struct NewBadgeStyle;
+impl container::StyleSheet for NewBadgeStyle {
+    type Style = Theme;
+    fn appearance(&self, _: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(iced::Background::Color(BORDER_GLOW)),
+            border: iced::Border {
+                color: Color::TRANSPARENT,
+                width: 0.0,
+                radius: 6.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+struct LetterPlaceholderStyle(Color);
 impl container::StyleSheet for LetterPlaceholderStyle {
     type Style = Theme;
     fn appearance(&self, _: &Self::Style) -> container::Appearance {
         container::Appearance {
-            background: Some(iced::Background::Color(ICON_BG)),
+            background: Some(iced::Background::Color(self.0)),
             border: iced::Border {
                 color: Color::TRANSPARENT,
                 width: 0.0,
@@ -561,6 +2244,56 @@ impl container::StyleSheet for IconContainerStyle {
     }
 }
 
+struct ContextMenuStyle;
+impl container::StyleSheet for ContextMenuStyle {
+    type Style = Theme;
+    fn appearance(&self, _: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(iced::Background::Color(BG_SEARCH)),
+            border: iced::Border {
+                color: BORDER_PANEL,
+                width: 1.0,
+                radius: 8.0.into(),
+            },
+            shadow: iced::Shadow {
+                color: Color::from_rgba(0.0, 0.0, 0.0, 0.4),
+                offset: iced::Vector::new(0.0, 2.0),
+                blur_radius: 12.0,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+struct MenuItemStyle;
+impl button::StyleSheet for MenuItemStyle {
+    type Style = Theme;
+    fn active(&self, _: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            background: None,
+            border: iced::Border {
+                color: Color::TRANSPARENT,
+                width: 0.0,
+                radius: 6.0.into(),
+            },
+            text_color: TEXT_WHITE,
+            ..Default::default()
+        }
+    }
+    fn hovered(&self, _: &Self::Style) -> button::Appearance {
+        button::Appearance {
+            background: Some(iced::Background::Color(BG_SELECTED)),
+            border: iced::Border {
+                color: Color::TRANSPARENT,
+                width: 0.0,
+                radius: 6.0.into(),
+            },
+            text_color: TEXT_WHITE,
+            ..Default::default()
+        }
+    }
+}
+
 struct TitleBarButtonStyle;
 impl button::StyleSheet for TitleBarButtonStyle {
     type Style = Theme;
@@ -618,3 +2351,256 @@ impl button::StyleSheet for CloseButtonStyle {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "C:/Program Files/JetBrains/IntelliJ IDEA/bin/idea64.exe";
+
+    fn result(display_name: &str, base_name: Option<&str>) -> ProgramResult {
+        ProgramResult {
+            path: PathBuf::from(format!("C:/Apps/{}.exe", display_name)),
+            display_name: display_name.to_string(),
+            base_name: base_name.map(str::to_string),
+            icon_path: None,
+            icon_ready: false,
+            source: ProgramSource::StartMenu,
+            extension: Some("exe".to_string()),
+            target: None,
+            pinned: false,
+            opener: None,
+            action: ResultAction::Launch,
+            is_new: false,
+            match_indices: Vec::new(),
+            exists: true,
+        }
+    }
+
+    /// An app on the default config, with its cache in a fresh temp folder
+    fn app(name: &str) -> App {
+        let dir = std::env::temp_dir().join(format!("locksearch-ui-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = Config { cache_dir: Some(dir.to_string_lossy().into_owned()), ..Config::default() };
+        App::new(config).0
+    }
+
+    #[tokio::test]
+    async fn an_earlier_error_timer_leaves_a_newer_error_up() {
+        let mut app = app("errors");
+        let _ = app.show_error("first".to_string());
+        let first = app.error_generation;
+        let _ = app.show_error("second".to_string());
+
+        let _ = app.update(Message::ClearLaunchError(first));
+        assert_eq!(app.launch_error.as_deref(), Some("second"));
+        let _ = app.update(Message::ClearLaunchError(app.error_generation));
+        assert_eq!(app.launch_error, None);
+    }
+
+    #[test]
+    fn show_more_reveals_held_results_and_searches_only_past_them() {
+        let mut app = app("show-more");
+        app.config.max_results = 2;
+        let names = |app: &App| app.shown_results().iter().map(|r| r.display_name.clone()).collect::<Vec<_>>();
+        let searches = |app: &App| app.query_id.load(Ordering::SeqCst);
+
+        // The first page's search found one result past it, and nothing more
+        let mut web = result("Search the web", None);
+        web.source = ProgramSource::Provider;
+        app.search_results = vec![result("A", None), web, result("B", None)];
+        let _ = app.update(Message::ShowMore);
+        assert_eq!(names(&app), ["A", "B", "Search the web"]);
+        assert_eq!(searches(&app), 0);
+
+        // A full page past the first: the one after it is fetched
+        app.reset_selection();
+        app.search_results = ["A", "B", "C", "D"].iter().map(|name| result(name, None)).collect();
+        let _ = app.update(Message::ShowMore);
+        assert_eq!(names(&app), ["A", "B", "C", "D"]);
+        assert_eq!(searches(&app), 1);
+    }
+
+    #[test]
+    fn toggling_a_pin_updates_the_list_and_the_glyphs() {
+        let mut results = vec![result("Python (Python311)", Some("Python")), result("Python (Python312)", Some("Python"))];
+        let mut pinned = vec!["Notepad".to_string()];
+        let names: Vec<String> = results[0].config_names().map(str::to_string).collect();
+
+        set_pinned(&mut pinned, &names, true);
+        mark_pinned(&mut results, &normalized_names(&pinned));
+        assert_eq!(pinned, ["Notepad", "Python (Python311)"]);
+        assert_eq!(results.iter().map(|r| r.pinned).collect::<Vec<_>>(), [true, false]);
+
+        // A pin from before the names were told apart covers both, and unpinning either drops it
+        pinned = vec!["python".to_string()];
+        mark_pinned(&mut results, &normalized_names(&pinned));
+        assert_eq!(results.iter().map(|r| r.pinned).collect::<Vec<_>>(), [true, true]);
+        set_pinned(&mut pinned, &names, false);
+        mark_pinned(&mut results, &normalized_names(&pinned));
+        assert!(pinned.is_empty());
+        assert_eq!(results.iter().map(|r| r.pinned).collect::<Vec<_>>(), [false, false]);
+    }
+
+    #[test]
+    fn keys_pick_the_binding_needing_the_most_held_modifiers() {
+        use keyboard::key::Named;
+        use keyboard::Modifiers;
+        let enter = keyboard::Key::Named(Named::Enter);
+        assert_eq!(key_action(&enter, Modifiers::empty()), Some(KeyAction::Activate));
+        assert_eq!(key_action(&enter, Modifiers::SHIFT), Some(KeyAction::Activate));
+        assert_eq!(key_action(&enter, Modifiers::CTRL), Some(KeyAction::RevealFolder));
+        assert_eq!(key_action(&enter, Modifiers::CTRL | Modifiers::SHIFT), Some(KeyAction::RunElevated));
+        let p = keyboard::Key::Character("p".into());
+        assert_eq!(key_action(&p, Modifiers::CTRL), Some(KeyAction::TogglePin));
+        assert_eq!(key_action(&p, Modifiers::empty()), None);
+        let delete = keyboard::Key::Named(Named::Delete);
+        assert_eq!(key_action(&delete, Modifiers::empty()), None);
+    }
+
+    #[test]
+    fn help_lists_every_binding_once_in_the_chosen_language() {
+        let strings = Strings::load(Some("de"));
+        let labels: Vec<String> = KEY_BINDINGS.iter().map(|binding| key_label(binding, &strings)).collect();
+        assert!(labels.contains(&"Strg+Umschalt+Eingabe".to_string()));
+        assert!(labels.contains(&"Umschalt+Entf".to_string()));
+        assert!(labels.contains(&"F5".to_string()));
+        let unique: HashSet<&String> = labels.iter().collect();
+        assert_eq!(unique.len(), labels.len());
+        assert!(KEY_BINDINGS.iter().all(|binding| !strings.key_action(binding.action).is_empty()));
+    }
+
+    fn ellipsis() -> String {
+        format!("…{}", std::path::MAIN_SEPARATOR)
+    }
+
+    #[test]
+    fn breadcrumbs_keep_the_last_components() {
+        assert_eq!(breadcrumb(PATH, 2), (ellipsis(), vec!["bin", "idea64.exe"]));
+        assert_eq!(breadcrumb(PATH, 3), (ellipsis(), vec!["IntelliJ IDEA", "bin", "idea64.exe"]));
+    }
+
+    #[test]
+    fn short_enough_breadcrumbs_are_whole() {
+        let all = vec!["C:", "Program Files", "JetBrains", "IntelliJ IDEA", "bin", "idea64.exe"];
+        assert_eq!(breadcrumb(PATH, 0), (String::new(), all.clone()));
+        assert_eq!(breadcrumb(PATH, 6), (String::new(), all.clone()));
+        assert_eq!(breadcrumb(PATH, 10), (String::new(), all));
+        // Leading separators of a network path stay
+        assert_eq!(breadcrumb(r"\\server\share\app.exe", 3), (r"\\".to_string(), vec!["server", "share", "app.exe"]));
+    }
+
+    #[test]
+    fn breadcrumbs_highlight_the_folders_the_search_matched() {
+        let path = Path::new(PATH);
+        assert_eq!(breadcrumb_highlights("jb ij", path, 3), [true, false, false]);
+        assert_eq!(breadcrumb_highlights("jb ij", path, 4), [true, true, false, false]);
+        // Out of order, the search doesn't match the folders, so nothing is highlighted
+        assert_eq!(breadcrumb_highlights("ij jb", path, 4), [false; 4]);
+        // Single words are left to the name match
+        assert_eq!(breadcrumb_highlights("bin", path, 2), [false; 2]);
+    }
+
+    #[test]
+    fn the_selection_follows_its_path_and_falls_back_to_the_top() {
+        let results = vec![result("Alpha", None), result("Beta", None), result("Gamma", None)];
+        let beta = PathBuf::from("C:/Apps/Beta.exe");
+        assert_eq!(followed_index(&results, Some(&beta)), 1);
+
+        // Beta moved up after a refresh
+        let refreshed = vec![result("Beta", None), result("Gamma", None)];
+        assert_eq!(followed_index(&refreshed, Some(&beta)), 0);
+        let gamma = PathBuf::from("C:/Apps/Gamma.exe");
+        assert_eq!(followed_index(&refreshed, Some(&gamma)), 1);
+
+        // Gone, or nothing followed
+        let gone = PathBuf::from("C:/Apps/Delta.exe");
+        assert_eq!(followed_index(&refreshed, Some(&gone)), 0);
+        assert_eq!(followed_index(&refreshed, None), 0);
+        assert_eq!(followed_index(&[], Some(&beta)), 0);
+    }
+
+    #[test]
+    fn placeholder_glyphs_keep_whole_characters() {
+        assert_eq!(placeholder_glyph("firefox"), "F");
+        assert_eq!(placeholder_glyph("  notepad"), "N");
+        assert_eq!(placeholder_glyph("微信"), "微");
+        assert_eq!(placeholder_glyph("ärger"), "Ä");
+        // Joined and flag emoji are one grapheme each
+        assert_eq!(placeholder_glyph("👨‍👩‍👧 Family"), "👨‍👩‍👧");
+        assert_eq!(placeholder_glyph("🇯🇵 Travel"), "🇯🇵");
+        assert_eq!(placeholder_glyph(""), "?");
+        assert_eq!(placeholder_glyph(" \t"), "?");
+    }
+
+    #[test]
+    fn placeholder_colors_are_stable_per_name() {
+        for name in ["firefox", "微信", "👨‍👩‍👧 Family", ""] {
+            let color = placeholder_color(name);
+            assert_eq!(color, placeholder_color(name));
+            assert!(ICON_BG_PALETTE.contains(&color));
+        }
+        let names = ["alpha", "beta", "gamma", "delta", "微信", "🇯🇵 Travel"];
+        let first = placeholder_color(names[0]);
+        assert!(names.iter().any(|name| placeholder_color(name) != first));
+    }
+
+    #[test]
+    fn icons_extracted_later_upgrade_stored_results() {
+        let mut app = app("ready-icons");
+        app.search_results = vec![result("Alpha", None), result("Beta", None)];
+        let beta_icon = PathBuf::from("icons/beta.png");
+        let _ = app.update(Message::IconReady(PathBuf::from("C:/Apps/Beta.exe"), beta_icon.clone()));
+        // The visible row got it at once
+        assert!(app.search_results[1].icon_ready);
+
+        // A result ranked before the icon was ready picks it up when stored
+        let mut own = result("Beta", None);
+        own.icon_path = Some(PathBuf::from("icons/own.png"));
+        own.icon_ready = true;
+        let mut results = vec![result("Alpha", None), result("Beta", None)];
+        let mut kept = vec![own];
+        app.apply_ready_icons(&mut results);
+        app.apply_ready_icons(&mut kept);
+        assert_eq!((results[0].icon_path.as_ref(), results[0].icon_ready), (None, false));
+        assert_eq!((results[1].icon_path.as_ref(), results[1].icon_ready), (Some(&beta_icon), true));
+        // An icon it already had is left alone
+        assert_eq!(kept[0].icon_path, Some(PathBuf::from("icons/own.png")));
+    }
+
+    #[tokio::test]
+    async fn a_freshly_created_file_is_flagged_new() {
+        let dir = std::env::temp_dir().join(format!("locksearch-ui-new-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("fresh.exe"), b"").unwrap();
+        let options = IndexOptions {
+            index_start_menu: false,
+            index_program_files: false,
+            index_desktop: false,
+            index_downloads: false,
+            extra_paths: vec![(dir.clone(), 1)],
+            ..IndexOptions::default()
+        };
+        let index = ProgramIndex::in_memory();
+        let mut progress = index.start_indexing(options).await.unwrap();
+        while progress.changed().await.is_ok() {}
+
+        let mut entries = (*index.get_entries().await).clone();
+        let mut old = entries[0].clone();
+        old.path = dir.join("old.exe");
+        old.display_name = "old".to_string();
+        old.modified = Some(unix_now() - 30 * 24 * 60 * 60);
+        entries.push(old);
+        let ranked = SearchEngine::new().rank("", &entries, &HashMap::new(), 10, || false, |_| {}).unwrap();
+
+        let flagged = |days| -> Vec<(String, bool)> {
+            let results = program_results(ranked.clone(), new_since(days));
+            results.into_iter().map(|r| (r.display_name, r.is_new)).collect()
+        };
+        assert_eq!(flagged(7), [("fresh".to_string(), true), ("old".to_string(), false)]);
+        // Off by default
+        assert_eq!(flagged(0), [("fresh".to_string(), false), ("old".to_string(), false)]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}